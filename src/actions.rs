@@ -0,0 +1,979 @@
+use crate::sys_numa_info::{format_cpulist, parse_cpulist, parse_cpulist_bounded};
+use std::ffi::OsString;
+use std::os::unix::ffi::OsStringExt;
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::process::CommandExt;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+use std::{fs, io};
+
+// How long a relaunched process gets to exit after SIGTERM
+const RELAUNCH_EXIT_TIMEOUT: Duration = Duration::from_secs(5);
+
+// The task an action is applied to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActionTarget {
+    pub pid: u32,
+    pub tid: Option<u32>, // None applies to every thread of the process
+    pub name: String,
+}
+
+impl ActionTarget {
+    pub fn describe(&self) -> String {
+        match self.tid {
+            Some(tid) => format!("TID {} of PID {} ({})", tid, self.pid, self.name),
+            None => format!("PID {} ({})", self.pid, self.name),
+        }
+    }
+
+    // The whole process a thread belongs to, named after its main thread
+    pub fn process(self) -> ActionTarget {
+        if self.tid.is_none() {
+            return self;
+        }
+        let name = fs::read_to_string(format!("/proc/{}/comm", self.pid))
+            .map(|name| name.trim().to_string())
+            .unwrap_or(self.name);
+        ActionTarget {
+            pid: self.pid,
+            tid: None,
+            name,
+        }
+    }
+
+    // Threads the action has to be applied to one by one
+    pub fn tids(&self) -> io::Result<Vec<u32>> {
+        if let Some(tid) = self.tid {
+            return Ok(vec![tid]);
+        }
+        let mut tids: Vec<u32> = fs::read_dir(format!("/proc/{}/task", self.pid))?
+            .filter_map(Result::ok)
+            .filter_map(|entry| entry.file_name().to_str()?.parse().ok())
+            .collect();
+        tids.sort_unstable();
+        Ok(tids)
+    }
+}
+
+// sched_setaffinity(2) for a single thread
+pub fn set_cpu_affinity(tid: u32, cpus: &[u32]) -> io::Result<()> {
+    apply_cpu_set(tid, &cpu_set(cpus)?)
+}
+
+fn cpu_set(cpus: &[u32]) -> io::Result<libc::cpu_set_t> {
+    // SAFETY: cpu_set_t is a plain bitmask, all zeroes is the empty set
+    let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+    for cpu in cpus {
+        if *cpu as usize >= libc::CPU_SETSIZE as usize {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("CPU {} is out of range", cpu),
+            ));
+        }
+        // SAFETY: the CPU index was bounds-checked above
+        unsafe { libc::CPU_SET(*cpu as usize, &mut set) };
+    }
+    Ok(set)
+}
+
+// Only makes the syscall, so it is safe to run between fork and exec
+fn apply_cpu_set(tid: u32, set: &libc::cpu_set_t) -> io::Result<()> {
+    // SAFETY: `set` outlives the call and its size is passed along
+    let ret = unsafe {
+        libc::sched_setaffinity(
+            tid as libc::pid_t,
+            std::mem::size_of::<libc::cpu_set_t>(),
+            set,
+        )
+    };
+    if ret != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+// Pin every thread of the target, like `taskset -a -p`. Returns the number of threads changed.
+pub fn set_target_cpu_affinity(target: &ActionTarget, cpus: &[u32]) -> io::Result<usize> {
+    let mut changed = 0;
+    for tid in target.tids()? {
+        match set_cpu_affinity(tid, cpus) {
+            Ok(()) => changed += 1,
+            // Threads may exit while we walk the list
+            Err(e) if e.raw_os_error() == Some(libc::ESRCH) => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(changed)
+}
+
+// Node bitmask as the memory policy syscalls expect it: an array of unsigned longs
+const NODE_MASK_WORDS: usize = 16;
+const NODE_MASK_BITS: usize = NODE_MASK_WORDS * libc::c_ulong::BITS as usize;
+
+fn node_mask(nodes: &[u32]) -> io::Result<[libc::c_ulong; NODE_MASK_WORDS]> {
+    let word_bits = libc::c_ulong::BITS as usize;
+    let mut mask = [0; NODE_MASK_WORDS];
+    for node in nodes {
+        let node = *node as usize;
+        if node >= NODE_MASK_BITS {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("node {} is out of range", node),
+            ));
+        }
+        mask[node / word_bits] |= 1 << (node % word_bits);
+    }
+    Ok(mask)
+}
+
+// migrate_pages(2): move every page of the process on `from` nodes to `to` nodes.
+// Blocks until done, returns the number of pages that could not be moved.
+pub fn migrate_process_pages(pid: u32, from: &[u32], to: &[u32]) -> io::Result<u64> {
+    let old_nodes = node_mask(from)?;
+    let new_nodes = node_mask(to)?;
+
+    // SAFETY: both masks hold NODE_MASK_BITS bits and outlive the call. The kernel reads
+    // one bit less than maxnode, hence the + 1 (numactl does the same).
+    let ret = unsafe {
+        libc::syscall(
+            libc::SYS_migrate_pages,
+            pid as libc::pid_t,
+            (NODE_MASK_BITS + 1) as libc::c_ulong,
+            old_nodes.as_ptr(),
+            new_nodes.as_ptr(),
+        )
+    };
+    if ret < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(ret as u64)
+}
+
+// include/uapi/linux/mempolicy.h
+const MPOL_PREFERRED: libc::c_int = 1;
+const MPOL_BIND: libc::c_int = 2;
+const MPOL_INTERLEAVE: libc::c_int = 3;
+const MPOL_LOCAL: libc::c_int = 4;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MemoryPolicy {
+    Bind(Vec<u32>),       // Only allocate from these nodes
+    Preferred(u32),       // Try this node first, fall back to others
+    Interleave(Vec<u32>), // Round-robin pages across these nodes
+    Local,                // Allocate on the node the task runs on
+}
+
+impl MemoryPolicy {
+    // "bind 0-1", "preferred 1", "interleave 0,1" or "local", numactl spellings accepted
+    pub fn parse(input: &str) -> Result<MemoryPolicy, String> {
+        let mut parts = input.split_whitespace();
+        let mode = parts.next().unwrap_or_default().to_lowercase();
+        let nodes_str = parts.next().unwrap_or_default();
+        if parts.next().is_some() {
+            return Err("expected a policy and a node list".to_string());
+        }
+        let nodes = || {
+            let list = nodes_str.trim_start_matches(['N', 'n']);
+            if list.is_empty() {
+                return Err(format!("{} needs a node list", mode));
+            }
+            parse_cpulist_bounded(list, NODE_MASK_BITS as u32 - 1)
+                .map_err(|e| format!("invalid node list \"{}\": {}", list, e))
+        };
+
+        match mode.as_str() {
+            "bind" | "membind" => Ok(MemoryPolicy::Bind(nodes()?)),
+            "preferred" | "prefer" => match nodes()?.as_slice() {
+                [node] => Ok(MemoryPolicy::Preferred(*node)),
+                _ => Err("preferred takes a single node".to_string()),
+            },
+            "interleave" => Ok(MemoryPolicy::Interleave(nodes()?)),
+            "local" | "localalloc" if nodes_str.is_empty() => Ok(MemoryPolicy::Local),
+            "local" | "localalloc" => Err("local takes no nodes".to_string()),
+            _ => Err(format!("unknown policy \"{}\"", mode)),
+        }
+    }
+
+    pub fn nodes(&self) -> Vec<u32> {
+        match self {
+            MemoryPolicy::Bind(nodes) | MemoryPolicy::Interleave(nodes) => nodes.clone(),
+            MemoryPolicy::Preferred(node) => vec![*node],
+            MemoryPolicy::Local => Vec::new(),
+        }
+    }
+
+    // Spelled like the policy column of numa_maps: "bind:0-1", "prefer:1"
+    pub fn describe(&self) -> String {
+        let nodes = format_cpulist(&self.nodes());
+        match self {
+            MemoryPolicy::Bind(_) => format!("bind:{}", nodes),
+            MemoryPolicy::Preferred(_) => format!("prefer:{}", nodes),
+            MemoryPolicy::Interleave(_) => format!("interleave:{}", nodes),
+            MemoryPolicy::Local => "local".to_string(),
+        }
+    }
+
+    // The numactl option that starts a command under this policy
+    pub fn numactl_option(&self) -> String {
+        let nodes = format_cpulist(&self.nodes());
+        match self {
+            MemoryPolicy::Bind(_) => format!("--membind={}", nodes),
+            MemoryPolicy::Preferred(_) => format!("--preferred={}", nodes),
+            MemoryPolicy::Interleave(_) => format!("--interleave={}", nodes),
+            MemoryPolicy::Local => "--localalloc".to_string(),
+        }
+    }
+
+    fn mode(&self) -> libc::c_int {
+        match self {
+            MemoryPolicy::Bind(_) => MPOL_BIND,
+            MemoryPolicy::Preferred(_) => MPOL_PREFERRED,
+            MemoryPolicy::Interleave(_) => MPOL_INTERLEAVE,
+            MemoryPolicy::Local => MPOL_LOCAL,
+        }
+    }
+}
+
+// set_mempolicy(2) for the calling thread. The mask is built by the caller, so this
+// only makes the syscall and is safe to run between fork and exec.
+fn set_mempolicy(mode: libc::c_int, mask: &[libc::c_ulong; NODE_MASK_WORDS]) -> io::Result<()> {
+    // SAFETY: the mask holds NODE_MASK_BITS bits, see migrate_process_pages for the + 1
+    let ret = unsafe {
+        libc::syscall(
+            libc::SYS_set_mempolicy,
+            mode,
+            mask.as_ptr(),
+            (NODE_MASK_BITS + 1) as libc::c_ulong,
+        )
+    };
+    if ret < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+// A command line as read back from /proc, to start it again
+#[derive(Debug, Default)]
+pub struct LaunchSpec {
+    pub program: Option<PathBuf>, // None looks argv[0] up in PATH
+    pub argv: Vec<OsString>,
+    pub cwd: Option<PathBuf>,
+    pub env: Option<Vec<(OsString, OsString)>>, // None inherits numatop's environment
+    pub cpus: Option<Vec<u32>>,                 // None inherits numatop's affinity
+    pub credentials: Option<Credentials>,       // None runs as numatop's user
+    pub stdio: [Option<fs::File>; 3],           // stdin, stdout, stderr, None is /dev/null
+}
+
+// Who a process runs as, from the Uid, Gid and Groups lines of /proc/PID/status
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Credentials {
+    pub uid: libc::uid_t, // Real UID, a setuid binary raises it again on exec
+    pub gid: libc::gid_t, // Real GID
+    pub groups: Vec<libc::gid_t>,
+}
+
+fn read_nul_separated(path: String) -> io::Result<Vec<OsString>> {
+    Ok(fs::read(path)?
+        .split(|b| *b == 0)
+        .filter(|arg| !arg.is_empty())
+        .map(|arg| OsString::from_vec(arg.to_vec()))
+        .collect())
+}
+
+impl LaunchSpec {
+    // Arguments, working directory and environment of a running process
+    pub fn from_process(pid: u32) -> io::Result<LaunchSpec> {
+        let argv = read_nul_separated(format!("/proc/{}/cmdline", pid))?;
+        if argv.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "kernel threads and zombies have no command line to relaunch",
+            ));
+        }
+        let env = read_nul_separated(format!("/proc/{}/environ", pid))?
+            .into_iter()
+            .filter_map(|var| {
+                let var = var.into_vec();
+                let split = var.iter().position(|b| *b == b'=')?;
+                Some((
+                    OsString::from_vec(var[..split].to_vec()),
+                    OsString::from_vec(var[split + 1..].to_vec()),
+                ))
+            })
+            .collect();
+
+        let mut cpus = None;
+        let mut uid = None;
+        let mut gid = None;
+        let mut groups = Vec::new();
+        for line in fs::read_to_string(format!("/proc/{}/status", pid))?.lines() {
+            // Uid and Gid list real, effective, saved set and filesystem IDs
+            let first_id = |value: &str| value.split_whitespace().next()?.parse().ok();
+            if let Some(value) = line.strip_prefix("Uid:") {
+                uid = first_id(value);
+            } else if let Some(value) = line.strip_prefix("Gid:") {
+                gid = first_id(value);
+            } else if let Some(value) = line.strip_prefix("Groups:") {
+                groups = value
+                    .split_whitespace()
+                    .filter_map(|group| group.parse().ok())
+                    .collect();
+            } else if let Some(value) = line.strip_prefix("Cpus_allowed_list:") {
+                cpus = Some(parse_cpulist(value));
+            }
+        }
+        let (Some(uid), Some(gid)) = (uid, gid) else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("no Uid or Gid in /proc/{}/status", pid),
+            ));
+        };
+
+        Ok(LaunchSpec {
+            // Daemons often rewrite argv[0] ("nginx: master process"), exe is the real binary
+            program: fs::read_link(format!("/proc/{}/exe", pid)).ok(),
+            argv,
+            cwd: fs::read_link(format!("/proc/{}/cwd", pid)).ok(),
+            env: Some(env),
+            cpus,
+            credentials: Some(Credentials { uid, gid, groups }),
+            stdio: Default::default(),
+        })
+    }
+
+    // Reopen the process's stdin, stdout and stderr through /proc, so the new process reads
+    // and writes where the old one did. Must happen while it still runs, or the other end of
+    // a pipe may be gone. Sockets can't be reopened and fall back to /dev/null.
+    pub fn reopen_stdio(&mut self, pid: u32) {
+        let open = |fd: u32, write: bool| {
+            fs::OpenOptions::new()
+                .read(!write)
+                .append(write)
+                .open(format!("/proc/{}/fd/{}", pid, fd))
+                .ok()
+        };
+        self.stdio = [open(0, false), open(1, true), open(2, true)];
+    }
+}
+
+// A word the shell passes on unchanged: no quotes, globs, variables or operators
+fn is_plain_word(arg: &str) -> bool {
+    !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./=:,+@%".contains(c))
+}
+
+// Quote an argument for a POSIX shell, leaving plain words alone
+pub fn shell_quote(arg: &str) -> String {
+    if is_plain_word(arg) {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+impl LaunchSpec {
+    // A command typed by the user. Plain words are run directly, so the new PID is the
+    // command itself. Anything else goes through `sh -c` for its pipes, globs and variables,
+    // and the PID is the shell's.
+    pub fn from_command_line(command: &str) -> LaunchSpec {
+        let words: Vec<&str> = command.split_whitespace().collect();
+        // A leading NAME=value is a variable assignment for the shell
+        let plain = !words.is_empty()
+            && !words[0].contains('=')
+            && words.iter().all(|word| is_plain_word(word));
+        let argv = if plain {
+            words.into_iter().map(OsString::from).collect()
+        } else {
+            vec!["sh".into(), "-c".into(), command.into()]
+        };
+        LaunchSpec {
+            argv,
+            ..Default::default()
+        }
+    }
+
+    // The command line as it would be typed into a shell
+    pub fn shell_command(&self) -> String {
+        let program = match &self.program {
+            Some(program) => program.as_os_str(),
+            None => &self.argv[0],
+        };
+        std::iter::once(program)
+            .chain(self.argv[1..].iter().map(|arg| arg.as_os_str()))
+            .map(|arg| shell_quote(&arg.to_string_lossy()))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    // The command line started under `numactl` as the same user with the same CPUs
+    pub fn numactl_command(&self, policy: &MemoryPolicy) -> String {
+        let mut command = String::new();
+        if let Some(credentials) = &self.credentials {
+            let groups = if credentials.groups.is_empty() {
+                "--clear-groups".to_string()
+            } else {
+                let groups: Vec<String> =
+                    credentials.groups.iter().map(|g| g.to_string()).collect();
+                format!("--groups={}", groups.join(","))
+            };
+            command += &format!(
+                "setpriv --reuid={} --regid={} {} ",
+                credentials.uid, credentials.gid, groups
+            );
+        }
+        command += "numactl ";
+        if let Some(cpus) = &self.cpus {
+            command += &format!("--physcpubind={} ", format_cpulist(cpus));
+        }
+        command + &format!("{} -- {}", policy.numactl_option(), self.shell_command())
+    }
+}
+
+// Start a command in its own session with the CPU affinity, user and stdio of the spec and
+// the given memory policy, like `numactl --physcpubind --membind`
+pub fn spawn_with_placement(spec: &LaunchSpec, policy: Option<&MemoryPolicy>) -> io::Result<u32> {
+    let stdio = |file: &Option<fs::File>| -> io::Result<Stdio> {
+        Ok(match file {
+            Some(file) => file.try_clone()?.into(),
+            None => Stdio::null(),
+        })
+    };
+    let mut command = match &spec.program {
+        Some(program) => Command::new(program),
+        None => Command::new(&spec.argv[0]),
+    };
+    command
+        .arg0(&spec.argv[0])
+        .args(&spec.argv[1..])
+        .stdin(stdio(&spec.stdio[0])?)
+        .stdout(stdio(&spec.stdio[1])?)
+        .stderr(stdio(&spec.stdio[2])?);
+    if let Some(cwd) = &spec.cwd {
+        command.current_dir(cwd);
+    }
+    if let Some(env) = &spec.env {
+        command.env_clear().envs(env.iter().map(|(k, v)| (k, v)));
+    }
+
+    // Nothing may allocate between fork and exec, so the masks are built here and the
+    // closure only makes syscalls
+    let cpu_mask = spec.cpus.as_deref().map(cpu_set).transpose()?;
+    let policy_mask = match policy {
+        Some(policy) => Some((policy.mode(), node_mask(&policy.nodes())?)),
+        None => None,
+    };
+    // Only root can switch users, anyone else keeps their own
+    // SAFETY: geteuid has no preconditions
+    let credentials = match &spec.credentials {
+        Some(credentials) if unsafe { libc::geteuid() } == 0 => Some(credentials.clone()),
+        Some(credentials) if credentials.uid != unsafe { libc::geteuid() } => {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("only root can start a process as UID {}", credentials.uid),
+            ));
+        }
+        _ => None,
+    };
+    // SAFETY: the closure only makes syscalls on memory prepared above
+    unsafe {
+        command.pre_exec(move || {
+            if libc::setsid() < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            if let Some(set) = &cpu_mask {
+                apply_cpu_set(0, set)?;
+            }
+            if let Some((mode, mask)) = &policy_mask {
+                set_mempolicy(*mode, mask)?;
+            }
+            // Groups and GID first, dropping the UID takes away the right to change them
+            if let Some(credentials) = &credentials
+                && (libc::setgroups(credentials.groups.len(), credentials.groups.as_ptr()) < 0
+                    || libc::setgid(credentials.gid) < 0
+                    || libc::setuid(credentials.uid) < 0)
+            {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+
+    let mut child = command.spawn()?;
+    let pid = child.id();
+    // Reap the child whenever it exits so it doesn't linger as a zombie
+    thread::spawn(move || child.wait());
+    Ok(pid)
+}
+
+// The kernel can't change another task's memory policy, so stop the process and start its
+// command line again under the new policy, as the same user, with the same CPU affinity and
+// stdio. Returns the PID of the new process.
+pub fn relaunch_with_policy(pid: u32, policy: &MemoryPolicy) -> io::Result<u32> {
+    let mut spec = LaunchSpec::from_process(pid)?;
+    // Refuse before stopping anything if it couldn't be started again
+    // SAFETY: geteuid has no preconditions
+    let euid = unsafe { libc::geteuid() };
+    if let Some(credentials) = &spec.credentials
+        && euid != 0
+        && credentials.uid != euid
+    {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!(
+                "PID {} belongs to UID {}, only root can relaunch it",
+                pid, credentials.uid
+            ),
+        ));
+    }
+    // An exe that was deleted or replaced reads as "/usr/bin/foo (deleted)"
+    let executable = spec.program.as_ref().is_some_and(|program| {
+        fs::metadata(program)
+            .is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+    });
+    if !executable {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!(
+                "the executable of PID {} can't be found, it would not start again",
+                pid
+            ),
+        ));
+    }
+    spec.reopen_stdio(pid);
+
+    // SAFETY: kill has no memory safety preconditions
+    if unsafe { libc::kill(pid as libc::pid_t, libc::SIGTERM) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let deadline = Instant::now() + RELAUNCH_EXIT_TIMEOUT;
+    while process_is_alive(pid) {
+        if Instant::now() >= deadline {
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                format!(
+                    "PID {} did not exit within {}s of SIGTERM. It may still exit and will \
+                     not be restarted",
+                    pid,
+                    RELAUNCH_EXIT_TIMEOUT.as_secs()
+                ),
+            ));
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+
+    spawn_with_placement(&spec, Some(policy))
+}
+
+// Exited processes linger as zombies until reaped by their parent, count those as gone
+fn process_is_alive(pid: u32) -> bool {
+    match fs::read_to_string(format!("/proc/{}/stat", pid)) {
+        Ok(stat) => stat
+            .rsplit_once(')')
+            .and_then(|(_, rest)| rest.split_whitespace().next())
+            .is_some_and(|state| state != "Z" && state != "X"),
+        Err(_) => false,
+    }
+}
+
+// EPERM alone doesn't say what is missing
+pub fn describe_action_error(e: &io::Error) -> String {
+    match e.raw_os_error() {
+        Some(libc::EPERM) | Some(libc::EACCES) => format!(
+            "{} (run numatop as root, or as the owner of the task for lowering priority and signals)",
+            e
+        ),
+        Some(libc::ESRCH) => "the task has exited".to_string(),
+        _ => e.to_string(),
+    }
+}
+
+// Signals offered by the signal dialog
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Signal {
+    Term,
+    Kill,
+    Int,
+    Hup,
+    Stop,
+    Cont,
+}
+
+impl Signal {
+    pub const ALL: [Signal; 6] = [
+        Signal::Term,
+        Signal::Kill,
+        Signal::Int,
+        Signal::Hup,
+        Signal::Stop,
+        Signal::Cont,
+    ];
+
+    // "term", "SIGTERM", "15" or a word like "stop" and "continue"
+    pub fn parse(input: &str) -> Result<Signal, String> {
+        let input = input.trim().to_uppercase();
+        let name = input.strip_prefix("SIG").unwrap_or(&input);
+        let signal = match name {
+            "TERM" | "TERMINATE" => Signal::Term,
+            "KILL" => Signal::Kill,
+            "INT" | "INTERRUPT" => Signal::Int,
+            "HUP" | "HANGUP" => Signal::Hup,
+            "STOP" | "PAUSE" => Signal::Stop,
+            "CONT" | "CONTINUE" | "RESUME" => Signal::Cont,
+            _ => match name.parse::<libc::c_int>() {
+                Ok(number) => Signal::ALL
+                    .into_iter()
+                    .find(|signal| signal.number() == number)
+                    .ok_or_else(|| format!("signal {} is not offered here", number))?,
+                Err(_) => return Err(format!("unknown signal \"{}\"", input)),
+            },
+        };
+        Ok(signal)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Signal::Term => "SIGTERM",
+            Signal::Kill => "SIGKILL",
+            Signal::Int => "SIGINT",
+            Signal::Hup => "SIGHUP",
+            Signal::Stop => "SIGSTOP",
+            Signal::Cont => "SIGCONT",
+        }
+    }
+
+    // As kill -s takes it
+    pub fn short_name(&self) -> &'static str {
+        self.name().trim_start_matches("SIG")
+    }
+
+    pub fn number(&self) -> libc::c_int {
+        match self {
+            Signal::Term => libc::SIGTERM,
+            Signal::Kill => libc::SIGKILL,
+            Signal::Int => libc::SIGINT,
+            Signal::Hup => libc::SIGHUP,
+            Signal::Stop => libc::SIGSTOP,
+            Signal::Cont => libc::SIGCONT,
+        }
+    }
+}
+
+// kill(2) for the whole process. Stop, continue and fatal signals act on every thread
+// anyway, so there is no single-thread variant.
+pub fn send_signal(target: &ActionTarget, signal: Signal) -> io::Result<()> {
+    // SAFETY: kill has no memory safety preconditions
+    if unsafe { libc::kill(target.pid as libc::pid_t, signal.number()) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+// include/uapi/linux/ioprio.h
+const IOPRIO_WHO_PROCESS: libc::c_int = 1;
+const IOPRIO_CLASS_SHIFT: u32 = 13;
+const IOPRIO_CLASS_RT: u32 = 1;
+const IOPRIO_CLASS_BE: u32 = 2;
+const IOPRIO_CLASS_IDLE: u32 = 3;
+const IOPRIO_MAX_LEVEL: u8 = 7;
+
+// I/O scheduling class and level, as set by ionice
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IoPriority {
+    None, // Derived from the nice value
+    RealTime(u8),
+    BestEffort(u8),
+    Idle,
+}
+
+impl IoPriority {
+    fn from_raw(raw: u32) -> IoPriority {
+        let level = (raw & ((1 << IOPRIO_CLASS_SHIFT) - 1)) as u8;
+        match raw >> IOPRIO_CLASS_SHIFT {
+            IOPRIO_CLASS_RT => IoPriority::RealTime(level),
+            IOPRIO_CLASS_BE => IoPriority::BestEffort(level),
+            IOPRIO_CLASS_IDLE => IoPriority::Idle,
+            _ => IoPriority::None,
+        }
+    }
+
+    fn to_raw(self) -> u32 {
+        match self {
+            IoPriority::None => 0,
+            IoPriority::RealTime(level) => IOPRIO_CLASS_RT << IOPRIO_CLASS_SHIFT | level as u32,
+            IoPriority::BestEffort(level) => IOPRIO_CLASS_BE << IOPRIO_CLASS_SHIFT | level as u32,
+            IoPriority::Idle => IOPRIO_CLASS_IDLE << IOPRIO_CLASS_SHIFT,
+        }
+    }
+
+    // The ionice options that set this priority
+    pub fn ionice_options(&self) -> String {
+        match self {
+            IoPriority::None => "-c 0".to_string(),
+            IoPriority::RealTime(level) => format!("-c 1 -n {}", level),
+            IoPriority::BestEffort(level) => format!("-c 2 -n {}", level),
+            IoPriority::Idle => "-c 3".to_string(),
+        }
+    }
+
+    // "be/4", "rt/0", "idle", "none"
+    pub fn describe(&self) -> String {
+        match self {
+            IoPriority::None => "none".to_string(),
+            IoPriority::RealTime(level) => format!("rt/{}", level),
+            IoPriority::BestEffort(level) => format!("be/{}", level),
+            IoPriority::Idle => "idle".to_string(),
+        }
+    }
+}
+
+// ioprio_get(2) for a single thread
+pub fn get_io_priority(tid: u32) -> io::Result<IoPriority> {
+    // SAFETY: ioprio_get has no memory safety preconditions
+    let ret =
+        unsafe { libc::syscall(libc::SYS_ioprio_get, IOPRIO_WHO_PROCESS, tid as libc::pid_t) };
+    if ret < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(IoPriority::from_raw(ret as u32))
+}
+
+// New nice value and/or I/O priority, like `renice` and `ionice` combined
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PriorityChange {
+    pub nice: Option<i64>,
+    pub io: Option<IoPriority>,
+}
+
+impl PriorityChange {
+    // "5", "be 4", "idle", "-5 rt 0": a nice value, an I/O class with its level, or both
+    pub fn parse(input: &str) -> Result<PriorityChange, String> {
+        let mut change = PriorityChange {
+            nice: None,
+            io: None,
+        };
+        let mut parts = input.split_whitespace().peekable();
+        while let Some(part) = parts.next() {
+            if let Ok(nice) = part.parse::<i64>() {
+                if change.nice.is_some() {
+                    return Err("expected a single nice value".to_string());
+                }
+                if !(-20..=19).contains(&nice) {
+                    return Err("nice values go from -20 to 19".to_string());
+                }
+                change.nice = Some(nice);
+                continue;
+            }
+
+            if change.io.is_some() {
+                return Err("expected a single I/O class".to_string());
+            }
+            let class = part.to_lowercase();
+            let mut level = || -> Result<u8, String> {
+                let level = match parts.next_if(|next| next.parse::<u8>().is_ok()) {
+                    Some(level) => level.parse::<u8>().unwrap_or_default(),
+                    None => return Err(format!("{} needs a level from 0 to 7", class)),
+                };
+                if level > IOPRIO_MAX_LEVEL {
+                    return Err(format!("{} needs a level from 0 to 7", class));
+                }
+                Ok(level)
+            };
+            change.io = Some(match class.as_str() {
+                "rt" | "realtime" => IoPriority::RealTime(level()?),
+                "be" | "best-effort" => IoPriority::BestEffort(level()?),
+                "idle" => IoPriority::Idle,
+                "none" => IoPriority::None,
+                _ => return Err(format!("unknown I/O class \"{}\"", part)),
+            });
+        }
+
+        if change.nice.is_none() && change.io.is_none() {
+            return Err("type a nice value, an I/O class, or both".to_string());
+        }
+        Ok(change)
+    }
+
+    // "nice 5, io be/4"
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(nice) = self.nice {
+            parts.push(format!("nice {}", nice));
+        }
+        if let Some(io) = self.io {
+            parts.push(format!("io {}", io.describe()));
+        }
+        parts.join(", ")
+    }
+}
+
+// Nice values and I/O priorities are per thread on Linux, so apply them to every thread of
+// the target like `renice -p` on each TID. Returns the number of threads changed.
+pub fn set_target_priority(target: &ActionTarget, change: &PriorityChange) -> io::Result<usize> {
+    let mut changed = 0;
+    for tid in target.tids()? {
+        match set_thread_priority(tid, change) {
+            Ok(()) => changed += 1,
+            // Threads may exit while we walk the list
+            Err(e) if e.raw_os_error() == Some(libc::ESRCH) => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(changed)
+}
+
+fn set_thread_priority(tid: u32, change: &PriorityChange) -> io::Result<()> {
+    if let Some(nice) = change.nice {
+        // SAFETY: setpriority has no memory safety preconditions
+        let ret = unsafe {
+            libc::setpriority(libc::PRIO_PROCESS, tid as libc::id_t, nice as libc::c_int)
+        };
+        if ret != 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    if let Some(io) = change.io {
+        // SAFETY: ioprio_set has no memory safety preconditions
+        let ret = unsafe {
+            libc::syscall(
+                libc::SYS_ioprio_set,
+                IOPRIO_WHO_PROCESS,
+                tid as libc::pid_t,
+                io.to_raw() as libc::c_int,
+            )
+        };
+        if ret < 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signal_parse_accepts_names_numbers_and_words() {
+        for (input, signal) in [
+            ("term", Signal::Term),
+            ("SIGTERM", Signal::Term),
+            ("15", Signal::Term),
+            ("kill", Signal::Kill),
+            ("9", Signal::Kill),
+            (" sigint ", Signal::Int),
+            ("hangup", Signal::Hup),
+            ("pause", Signal::Stop),
+            ("Continue", Signal::Cont),
+            ("SIGCONT", Signal::Cont),
+        ] {
+            assert_eq!(Signal::parse(input), Ok(signal), "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn signal_parse_rejects_signals_not_offered() {
+        for input in ["", "usr1", "SIG", "10", "-9", "99999999999"] {
+            assert!(Signal::parse(input).is_err(), "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn priority_change_parse_accepts_nice_io_class_or_both() {
+        let change = |nice, io| PriorityChange { nice, io };
+        for (input, expected) in [
+            ("5", change(Some(5), None)),
+            ("-20", change(Some(-20), None)),
+            ("19", change(Some(19), None)),
+            ("be 4", change(None, Some(IoPriority::BestEffort(4)))),
+            (
+                "best-effort 0",
+                change(None, Some(IoPriority::BestEffort(0))),
+            ),
+            ("RT 7", change(None, Some(IoPriority::RealTime(7)))),
+            ("idle", change(None, Some(IoPriority::Idle))),
+            ("none", change(None, Some(IoPriority::None))),
+            ("-5 be 2", change(Some(-5), Some(IoPriority::BestEffort(2)))),
+            ("be 2 -5", change(Some(-5), Some(IoPriority::BestEffort(2)))),
+            ("10 idle", change(Some(10), Some(IoPriority::Idle))),
+        ] {
+            assert_eq!(PriorityChange::parse(input), Ok(expected), "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn priority_change_parse_rejects_out_of_range_values() {
+        for input in [
+            "",
+            "-21",
+            "20",
+            "be",
+            "be 8",
+            "rt 255",
+            "be -1",
+            "be x",
+            "1 2",
+            "idle be 2",
+            "fast",
+        ] {
+            assert!(PriorityChange::parse(input).is_err(), "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn memory_policy_parse_accepts_numactl_spellings() {
+        for (input, policy) in [
+            ("bind 0-1", MemoryPolicy::Bind(vec![0, 1])),
+            ("membind N0,2", MemoryPolicy::Bind(vec![0, 2])),
+            ("preferred 1", MemoryPolicy::Preferred(1)),
+            ("prefer n1", MemoryPolicy::Preferred(1)),
+            ("Interleave 0,1", MemoryPolicy::Interleave(vec![0, 1])),
+            ("local", MemoryPolicy::Local),
+            ("localalloc", MemoryPolicy::Local),
+        ] {
+            assert_eq!(MemoryPolicy::parse(input), Ok(policy), "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn memory_policy_parse_rejects_bad_node_lists() {
+        for input in [
+            "",
+            "bind",
+            "bind N",
+            "bind 0 1",
+            "preferred 0-1",
+            "local 0",
+            "spread 0",
+            "bind 0-4000000000",
+            "interleave 1024",
+        ] {
+            assert!(MemoryPolicy::parse(input).is_err(), "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn from_command_line_runs_plain_words_directly() {
+        let spec = LaunchSpec::from_command_line("  stress-ng --vm 2 --vm-bytes=1G ");
+        assert_eq!(spec.argv, ["stress-ng", "--vm", "2", "--vm-bytes=1G"]);
+        assert_eq!(spec.shell_command(), "stress-ng --vm 2 --vm-bytes=1G");
+    }
+
+    #[test]
+    fn from_command_line_sends_shell_syntax_through_sh() {
+        for command in [
+            "yes | head",
+            "ls *.log",
+            "echo $HOME",
+            "FOO=1 env",
+            "echo 'a b'",
+            "sleep 1; true",
+        ] {
+            let spec = LaunchSpec::from_command_line(command);
+            assert_eq!(spec.argv, ["sh", "-c", command], "{:?}", command);
+        }
+    }
+}
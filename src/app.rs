@@ -1,16 +1,336 @@
-use crate::numa_node::NumaNode;
+use crate::actions::{
+    ActionTarget, LaunchSpec, MemoryPolicy, PriorityChange, Signal, describe_action_error,
+    get_io_priority, migrate_process_pages, relaunch_with_policy, send_signal,
+    set_target_cpu_affinity, set_target_priority, spawn_with_placement,
+};
+use crate::audit::{AuditLog, AuditOutcome};
+use crate::filter::ProcessFilter;
+use crate::history::MetricsHistory;
+use crate::io_stats::{RawIoCounters, get_io_device_samples};
+use crate::migrations::{MigrationTracker, RECENT_NODE_SAMPLES, ThreadMigrations};
+use crate::numa_balancing::{NumaSchedStats, get_numa_balancing_mode, parse_numa_sched_stats};
+use crate::numa_maps::{NumaMapping, dominant_policy, get_node_memory_kb, parse_numa_maps};
+use crate::numa_node::{IoDevice, IoDeviceKind, NodeNumaStat, NumaNode};
 use crate::proc_info::{
-    ProcessInfo, RawCpuTimes, get_processes_currently_on_core, parse_proc_stat_for_cores,
+    ProcessInfo, ProcessNumaMemory, RawCpuTimes, clock_ticks_per_sec, describe_task_state,
+    get_all_task_stats, get_process_info, get_process_numa_memory, parse_proc_stat_for_cores,
+    read_cgroup,
+};
+use crate::rebalance::{Move, Suggestion, suggest_moves};
+use crate::sys_numa_info::{
+    format_cpulist, get_all_present_cpu_indices, get_numa_node_data, parse_cpulist_bounded,
 };
-use crate::sys_numa_info::{get_all_present_cpu_indices, get_numa_node_data};
 use ratatui::layout::Rect;
-use std::collections::HashMap;
+use ratatui::widgets::TableState;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::io;
+use std::path::PathBuf;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+// Walking numa_maps of every process is expensive, so refresh the table less often than the CPUs
+const PROCESS_TABLE_REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+// Read-only builds can't be switched to changing system state
+pub const READ_ONLY_BUILD: bool = cfg!(feature = "read-only");
+// How long a status message stays in the bottom line
+const STATUS_MESSAGE_DURATION: Duration = Duration::from_secs(5);
+// Commands started with 'r' that are still listed in the launched panel
+const MAX_LAUNCHED_PROCESSES: usize = 5;
+// Ticks of node history kept for the task in the detail view
+pub const DETAIL_TIMELINE_TICKS: usize = RECENT_NODE_SAMPLES;
+// How far back the node sparklines go unless --history says otherwise
+pub const DEFAULT_HISTORY_RETENTION: Duration = Duration::from_secs(5 * 60);
+// The chart can't zoom in further than this many ticks
+const MIN_CHART_WINDOW_TICKS: usize = 20;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum View {
+    #[default]
+    Nodes,
+    Processes,
+    ProcessDetail,
+    Migrations,
+    Suggestions,
+    Chart,
+}
+
+#[derive(Debug, Default)]
+pub struct ProcessDetail {
+    pub return_view: View, // Where ESC goes back to: the process table or the popup
+    pub pid: u32,
+    pub tid: u32, // The selected thread, or the main thread when opened from the process table
+    pub name: String,
+    pub whole_process: bool, // Opened for a process rather than one of its threads
+    pub task: Option<ProcessInfo>, // None once the task has exited
+    pub cgroup: String,
+    pub node_timeline: VecDeque<Option<u32>>, // Node the task ran on at each tick, oldest first
+    pub mappings: Vec<NumaMapping>,
+    pub mappings_error: Option<String>, // Why numa_maps couldn't be read, shown instead
+    pub numa_sched: Option<NumaSchedStats>,
+    pub table_state: TableState,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputMode {
+    Normal,
+    Search, // Typing a '/' filter
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskChange {
+    Appeared, // Started running on the core since the previous tick
+    Departed, // Ran on the core at the previous tick, shown once more before being dropped
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PopupTarget {
+    Core(u32),
+    Node(u32),
+}
+
+// Optional per-task columns of the popup, toggled with the number keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PopupColumn {
+    User,
+    State,
+    Priority,
+    Nice,
+    Policy,
+    Rss,
+    Started,
+    Cmdline,
+}
+
+impl PopupColumn {
+    pub const ALL: [PopupColumn; 8] = [
+        PopupColumn::User,
+        PopupColumn::State,
+        PopupColumn::Priority,
+        PopupColumn::Nice,
+        PopupColumn::Policy,
+        PopupColumn::Rss,
+        PopupColumn::Started,
+        PopupColumn::Cmdline,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PopupColumn::User => "User",
+            PopupColumn::State => "State",
+            PopupColumn::Priority => "Prio",
+            PopupColumn::Nice => "Nice",
+            PopupColumn::Policy => "Policy",
+            PopupColumn::Rss => "RSS",
+            PopupColumn::Started => "Started",
+            PopupColumn::Cmdline => "Cmdline",
+        }
+    }
+}
+
+// Popup sort order; optional columns can only be sorted by while they are shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PopupSortKey {
+    Cpu,
+    Tid,
+    Name,
+    Column(PopupColumn),
+}
+
+impl PopupSortKey {
+    // Ascending order of two tasks by this key
+    pub fn compare(&self, a: &ProcessInfo, b: &ProcessInfo) -> Ordering {
+        match self {
+            PopupSortKey::Cpu => a.cpu_percent.total_cmp(&b.cpu_percent),
+            PopupSortKey::Tid => a.tid.cmp(&b.tid),
+            PopupSortKey::Name => a.thread_name.cmp(&b.thread_name),
+            PopupSortKey::Column(column) => match column {
+                PopupColumn::User => a.user.cmp(&b.user),
+                PopupColumn::State => a.state.cmp(&b.state),
+                PopupColumn::Priority => a.priority.cmp(&b.priority),
+                PopupColumn::Nice => a.nice.cmp(&b.nice),
+                PopupColumn::Policy => a.policy.cmp(&b.policy),
+                PopupColumn::Rss => a.rss_kb.cmp(&b.rss_kb),
+                PopupColumn::Started => a.start_time.cmp(&b.start_time),
+                PopupColumn::Cmdline => a.cmdline.cmp(&b.cmdline),
+            },
+        }
+    }
+
+    // Busiest and largest first, everything else alphabetically or oldest first
+    fn descending_by_default(&self) -> bool {
+        matches!(
+            self,
+            PopupSortKey::Cpu | PopupSortKey::Column(PopupColumn::Rss)
+        )
+    }
+}
+
+// A popup table row. When grouping, each process gets a header row above its threads.
+#[derive(Debug)]
+pub enum PopupRow<'a> {
+    Process(Vec<&'a ProcessInfo>), // The process's threads on the core or node
+    Task(&'a ProcessInfo),
+}
+
+// What the popup selection points at, so it follows the task as rows re-sort every tick
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PopupSelection {
+    Process(u32), // PID
+    Task(u32),    // TID
+}
+
+impl PopupRow<'_> {
+    pub fn tgid(&self) -> u32 {
+        match self {
+            PopupRow::Process(threads) => threads[0].tgid,
+            PopupRow::Task(task) => task.tgid,
+        }
+    }
+
+    pub fn selection(&self) -> PopupSelection {
+        match self {
+            PopupRow::Process(threads) => PopupSelection::Process(threads[0].tgid),
+            PopupRow::Task(task) => PopupSelection::Task(task.tid),
+        }
+    }
+}
+
+// One chart per metric in the chart view, each with a line per node
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChartMetric {
+    Cpu,
+    Memory,
+    RemoteAllocs,
+}
+
+impl ChartMetric {
+    pub const ALL: [ChartMetric; 3] = [
+        ChartMetric::Cpu,
+        ChartMetric::Memory,
+        ChartMetric::RemoteAllocs,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ChartMetric::Cpu => "CPU utilization %",
+            ChartMetric::Memory => "Memory used %",
+            ChartMetric::RemoteAllocs => "Remote allocations (pages/s)",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ChartState {
+    pub metrics: Vec<ChartMetric>, // Shown charts, in ChartMetric::ALL order
+    pub hidden_nodes: BTreeSet<u32>,
+    pub window: usize, // Ticks shown, up to the retained history
+}
 
 #[derive(Debug)]
 pub struct PopupState {
     pub show: bool,
-    pub cpu_core_id: u32,
+    pub target: PopupTarget,
     pub processes: Vec<ProcessInfo>,
+    pub group_by_process: bool, // Show threads nested under their process
+    pub sampled_at: Option<Instant>,
+    pub changes: HashMap<u32, TaskChange>, // Keyed by TID
+    pub process_memory_kb: HashMap<u32, BTreeMap<u32, u64>>, // Per-node residency, keyed by PID
+    pub memory_sampled_at: Option<Instant>,
+    pub columns: Vec<PopupColumn>, // Enabled optional columns, in PopupColumn::ALL order
+    pub sort_key: PopupSortKey,
+    pub sort_descending: bool,
+    pub table_state: TableState,
+    pub selection: Option<PopupSelection>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActionKind {
+    Affinity,
+    MigrateMemory,
+    MemoryPolicy,
+    Run(u32), // Start a command bound to this node
+    Signal,
+    Priority,
+}
+
+// Parsed parameter of an action, ready to be applied once confirmed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PendingAction {
+    SetAffinity(Vec<u32>),
+    MigrateMemory { from: Vec<u32>, to: Vec<u32> },
+    RelaunchWithPolicy(MemoryPolicy),
+    Run { command: String, node: u32 },
+    Signal(Signal),
+    SetPriority(PriorityChange),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DialogStage {
+    Input,   // Typing the new setting
+    Confirm, // Showing the current and new setting, y to apply
+}
+
+#[derive(Debug)]
+pub struct ActionDialog {
+    pub kind: ActionKind,
+    pub target: Option<ActionTarget>, // None for actions that don't act on a task, like run
+    pub stage: DialogStage,
+    pub input: String,
+    pub error: Option<String>, // Why the input was rejected
+    pub before: String,        // Current setting, e.g. "0-7 (N0 N1)"
+    pub after: String,         // New setting, filled in when confirming
+    pub command: String,       // Equivalent shell command, filled in when confirming
+    pub pending: Option<PendingAction>,
+}
+
+// A migrate_pages call running in the background, followed through numa_maps every tick
+#[derive(Debug)]
+pub struct MemoryMigration {
+    pub target: ActionTarget,
+    pub from: Vec<u32>,
+    pub to: Vec<u32>,
+    pub initial_kb: u64, // Memory on the source nodes when the migration started
+    pub remaining_kb: u64, // Memory still on the source nodes at the last tick
+    pub started: Instant,
+    command: String, // For the audit log once it finishes
+    handle: JoinHandle<io::Result<u64>>,
+}
+
+impl MemoryMigration {
+    pub fn progress(&self) -> f64 {
+        if self.initial_kb == 0 {
+            return 1.0;
+        }
+        (1.0 - self.remaining_kb as f64 / self.initial_kb as f64).clamp(0.0, 1.0)
+    }
+}
+
+// A command started with 'r', followed live in the launched panel
+#[derive(Debug, Clone)]
+pub struct LaunchedProcess {
+    pub pid: u32,
+    pub command: String,
+    pub node: u32, // Node its CPUs and memory are bound to
+    pub info: Option<ProcessInfo>,
+    pub node_memory_kb: BTreeMap<u32, u64>,
+    pub exited: bool, // Last info and memory are kept once it is gone
+}
+
+// A process being stopped and started again under a new memory policy
+#[derive(Debug)]
+pub struct PolicyRelaunch {
+    pub target: ActionTarget,
+    pub policy: MemoryPolicy,
+    command: String, // For the audit log once it finishes
+    handle: JoinHandle<io::Result<u32>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct StatusMessage {
+    pub text: String,
+    pub is_error: bool,
+    pub shown_at: Instant,
 }
 
 #[derive(Debug, Clone)]
@@ -19,30 +339,132 @@ pub struct CpuCoreArea {
     pub area: Rect,
 }
 
+#[derive(Debug, Clone)]
+pub struct NodeArea {
+    pub node_id: u32,
+    pub area: Rect,
+}
+
 pub struct App {
+    pub view: View,
+    pub input_mode: InputMode,
+    pub filter: ProcessFilter,
+    pub hide_kernel_threads: bool,
     pub numa_nodes: Vec<NumaNode>,
     pub prev_cpu_times: HashMap<u32, RawCpuTimes>,
+    pub prev_io_counters: HashMap<(IoDeviceKind, String), RawIoCounters>,
+    pub prev_io_sample_time: Option<Instant>,
+    pub prev_numastat: HashMap<u32, NodeNumaStat>,
+    pub prev_numastat_time: Option<Instant>,
+    pub history: MetricsHistory,
+    pub history_retention: Duration,
+    pub chart: ChartState,
     pub popup_state: PopupState,
+    pub process_memory: Vec<ProcessNumaMemory>,
+    pub process_memory_updated: Option<Instant>,
+    pub numa_balancing_mode: Option<u32>, // /proc/sys/kernel/numa_balancing
+    pub process_table_state: TableState,
+    pub process_detail: ProcessDetail,
+    pub migration_tracker: MigrationTracker,
+    pub migration_table_state: TableState,
+    pub suggestions: Vec<Suggestion>, // Largest locality gain first
+    pub suggestion_table_state: TableState,
+    pub action_dialog: Option<ActionDialog>,
+    pub status: Option<StatusMessage>, // Outcome of the last action
+    pub memory_migration: Option<MemoryMigration>,
+    pub policy_relaunch: Option<PolicyRelaunch>,
+    pub launched: Vec<LaunchedProcess>, // Oldest first
+    pub read_only: bool,                // Every action that changes system state is refused
+    pub dry_run: bool, // Confirmed actions are logged with their equivalent command, not applied
+    pub audit_log: Option<AuditLog>,
+    pub selected_node: u32, // Node cursor of the node view, moved with Left/Right
     pub cpu_core_areas: Vec<CpuCoreArea>,
+    pub node_areas: Vec<NodeArea>,
+    pub exit_requested: bool, // Quit once the background actions have finished
     pub should_exit: bool,
 }
 
+impl Default for App {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl App {
     pub fn new() -> App {
         App {
+            view: View::Nodes,
+            input_mode: InputMode::Normal,
+            filter: ProcessFilter::default(),
+            hide_kernel_threads: false,
             numa_nodes: vec![],
             prev_cpu_times: HashMap::new(),
+            prev_io_counters: HashMap::new(),
+            prev_io_sample_time: None,
+            prev_numastat: HashMap::new(),
+            prev_numastat_time: None,
+            history: MetricsHistory::new(history_samples(DEFAULT_HISTORY_RETENTION)),
+            history_retention: DEFAULT_HISTORY_RETENTION,
+            chart: ChartState {
+                metrics: ChartMetric::ALL.to_vec(),
+                hidden_nodes: BTreeSet::new(),
+                window: history_samples(DEFAULT_HISTORY_RETENTION),
+            },
             popup_state: PopupState {
                 show: false,
-                cpu_core_id: 0,
+                target: PopupTarget::Core(0),
                 processes: Vec::new(),
+                group_by_process: true,
+                sampled_at: None,
+                changes: HashMap::new(),
+                process_memory_kb: HashMap::new(),
+                memory_sampled_at: None,
+                columns: vec![PopupColumn::User, PopupColumn::State],
+                sort_key: PopupSortKey::Cpu,
+                sort_descending: true,
+                table_state: TableState::default(),
+                selection: None,
             },
+            process_memory: Vec::new(),
+            process_memory_updated: None,
+            numa_balancing_mode: None,
+            process_table_state: TableState::default(),
+            process_detail: ProcessDetail::default(),
+            migration_tracker: MigrationTracker::default(),
+            migration_table_state: TableState::default(),
+            suggestions: Vec::new(),
+            suggestion_table_state: TableState::default(),
+            action_dialog: None,
+            status: None,
+            memory_migration: None,
+            policy_relaunch: None,
+            launched: Vec::new(),
+            read_only: READ_ONLY_BUILD,
+            dry_run: false,
+            audit_log: AuditLog::default_path().map(AuditLog::new),
+            selected_node: 0,
             cpu_core_areas: Vec::new(),
+            node_areas: Vec::new(),
+            exit_requested: false,
             should_exit: false,
         }
     }
 
     pub fn update(&mut self) {
+        if self
+            .status
+            .as_ref()
+            .is_some_and(|status| status.shown_at.elapsed() >= STATUS_MESSAGE_DURATION)
+        {
+            self.status = None;
+        }
+        self.update_memory_migration();
+        self.update_policy_relaunch();
+        if self.exit_requested {
+            self.exit();
+        }
+        self.update_launched_processes();
+
         // update numa node memory utilization
         match get_numa_node_data() {
             Ok(nodes) => self.numa_nodes = nodes,
@@ -82,27 +504,1702 @@ impl App {
                 }
             }
         }
+
+        self.update_io_rates();
+        self.update_locality_rates();
+        self.history.record(&self.numa_nodes);
+        // The scan reads the stat file of every thread, so only run it while something uses it:
+        // the detail view's node timeline, and the views it is opened from for its first ticks
+        if self.popup_state.show
+            || matches!(
+                self.view,
+                View::Processes | View::ProcessDetail | View::Migrations | View::Suggestions
+            )
+        {
+            self.update_migrations();
+        }
+
+        if self.popup_state.show {
+            self.refresh_popup_processes();
+        }
+
+        match self.view {
+            View::Processes => self.update_process_memory(false),
+            View::ProcessDetail => self.update_process_detail(),
+            View::Suggestions => {
+                // Suggestions build on the process table, so follow its refresh interval
+                let updated = self.process_memory_updated;
+                self.update_process_memory(false);
+                if self.process_memory_updated != updated {
+                    self.update_suggestions();
+                }
+            }
+            View::Nodes | View::Migrations | View::Chart => {}
+        }
+    }
+
+    // Where each process's threads run, from the thread scan done for migrations
+    fn update_suggestions(&mut self) {
+        let cpu_nodes = self.cpu_node_map();
+        let mut thread_nodes: HashMap<u32, BTreeMap<u32, usize>> = HashMap::new();
+        for thread in self.migration_tracker.threads.values() {
+            if let Some(node) = cpu_nodes.get(&thread.cpu) {
+                *thread_nodes
+                    .entry(thread.tgid)
+                    .or_default()
+                    .entry(*node)
+                    .or_default() += 1;
+            }
+        }
+
+        // Keep the cursor on the same process as the list is recomputed
+        let selected_pid = self
+            .suggestion_table_state
+            .selected()
+            .and_then(|i| self.visible_suggestions().get(i).map(|s| s.pid));
+        self.suggestions = suggest_moves(&self.process_memory, &thread_nodes, &self.numa_nodes);
+        let visible = self.visible_suggestions();
+        let index = selected_pid
+            .and_then(|pid| visible.iter().position(|s| s.pid == pid))
+            .unwrap_or(0);
+        let index = (!visible.is_empty()).then(|| index.min(visible.len() - 1));
+        self.suggestion_table_state.select(index);
+    }
+
+    // Migrations can only be counted by watching every thread on every tick
+    fn update_migrations(&mut self) {
+        match get_all_task_stats() {
+            Ok(tasks) => {
+                let cpu_nodes = self.cpu_node_map();
+                self.migration_tracker.record(&tasks, &cpu_nodes);
+            }
+            Err(e) => {
+                eprintln!("Error fetching task stats: {}", e);
+            }
+        }
+    }
+
+    fn update_process_memory(&mut self, force: bool) {
+        let is_stale = self
+            .process_memory_updated
+            .is_none_or(|updated| updated.elapsed() >= PROCESS_TABLE_REFRESH_INTERVAL);
+        if !force && !is_stale {
+            return;
+        }
+
+        match get_process_numa_memory() {
+            Ok(processes) => self.process_memory = processes,
+            Err(e) => {
+                eprintln!("Error fetching process NUMA memory: {}", e);
+            }
+        }
+        self.process_memory_updated = Some(Instant::now());
+        self.numa_balancing_mode = get_numa_balancing_mode();
+
+        // Keep the selection on a valid row as processes come and go
+        if self.process_memory.is_empty() {
+            self.process_table_state.select(None);
+        } else {
+            let selected = self.process_table_state.selected().unwrap_or(0);
+            self.process_table_state
+                .select(Some(selected.min(self.process_memory.len() - 1)));
+        }
+    }
+
+    fn update_process_detail(&mut self) {
+        match parse_numa_maps(self.process_detail.pid) {
+            Ok(mappings) => {
+                self.process_detail.mappings = mappings;
+                self.process_detail.mappings_error = None;
+            }
+            Err(e) => {
+                self.process_detail.mappings.clear();
+                self.process_detail.mappings_error = Some(match e.kind() {
+                    io::ErrorKind::NotFound => "the process has exited".to_string(),
+                    io::ErrorKind::PermissionDenied => {
+                        format!("{} (run numatop as root or as the owner of the process)", e)
+                    }
+                    _ => e.to_string(),
+                });
+            }
+        }
+        self.process_detail.numa_sched = parse_numa_sched_stats(self.process_detail.pid)
+            .ok()
+            .flatten();
+        self.numa_balancing_mode = get_numa_balancing_mode();
+
+        let cpu_nodes = self.cpu_node_map();
+        let detail = &mut self.process_detail;
+        detail.task = get_process_info(detail.pid, detail.tid).ok();
+        if let Ok(cgroup) = read_cgroup(detail.pid) {
+            detail.cgroup = cgroup;
+        }
+
+        // Follow the tracker, which already knows where the task ran before the view opened.
+        // It forgets exited tasks, so keep their timeline going from here.
+        match self.migration_tracker.threads.get(&detail.tid) {
+            Some(thread) if detail.task.is_some() => {
+                detail.node_timeline = thread.recent_nodes.clone()
+            }
+            _ => {
+                let node = detail
+                    .task
+                    .as_ref()
+                    .and_then(|task| cpu_nodes.get(&task.cpu).copied());
+                let timeline = &mut detail.node_timeline;
+                timeline.push_back(node);
+                while timeline.len() > DETAIL_TIMELINE_TICKS {
+                    timeline.pop_front();
+                }
+            }
+        }
+    }
+
+    pub fn toggle_process_view(&mut self) {
+        self.view = match self.view {
+            View::Processes | View::ProcessDetail => View::Nodes,
+            _ => {
+                self.hide_popup();
+                self.update_process_memory(true);
+                View::Processes
+            }
+        };
+    }
+
+    pub fn toggle_migration_view(&mut self) {
+        self.view = match self.view {
+            View::Migrations => View::Nodes,
+            _ => {
+                self.hide_popup();
+                View::Migrations
+            }
+        };
+    }
+
+    pub fn toggle_suggestion_view(&mut self) {
+        self.view = match self.view {
+            View::Suggestions => View::Nodes,
+            _ => {
+                self.hide_popup();
+                self.update_process_memory(true);
+                self.update_suggestions();
+                View::Suggestions
+            }
+        };
+    }
+
+    pub fn toggle_chart_view(&mut self) {
+        self.view = match self.view {
+            View::Chart => View::Nodes,
+            _ => {
+                self.hide_popup();
+                View::Chart
+            }
+        };
+    }
+
+    pub fn toggle_chart_metric(&mut self, index: usize) {
+        let Some(metric) = ChartMetric::ALL.get(index) else {
+            return;
+        };
+        let metrics = &mut self.chart.metrics;
+        if let Some(position) = metrics.iter().position(|m| m == metric) {
+            metrics.remove(position);
+        } else {
+            metrics.push(*metric);
+            metrics.sort_by_key(|m| ChartMetric::ALL.iter().position(|all| all == m));
+        }
+    }
+
+    // Show or hide the lines of the node under the cursor
+    pub fn toggle_chart_node(&mut self) {
+        let node = self.selected_node;
+        if !self.chart.hidden_nodes.remove(&node) {
+            self.chart.hidden_nodes.insert(node);
+        }
+    }
+
+    // Halve or double the time window, within the retained history
+    pub fn zoom_chart(&mut self, zoom_in: bool) {
+        let window = if zoom_in {
+            self.chart.window / 2
+        } else {
+            self.chart.window * 2
+        };
+        self.chart.window = window.clamp(
+            MIN_CHART_WINDOW_TICKS.min(self.history.capacity),
+            self.history.capacity,
+        );
+    }
+
+    // Open the action dialog of the selected suggestion, already filled in and waiting
+    // for confirmation, so dry run, read-only mode and the audit log apply as usual
+    pub fn apply_selected_suggestion(&mut self) {
+        let Some(suggestion) = self
+            .suggestion_table_state
+            .selected()
+            .and_then(|i| self.visible_suggestions().get(i).map(|s| s.action))
+        else {
+            return;
+        };
+        let input = match suggestion {
+            Move::Cpus { to } => {
+                self.open_affinity_dialog();
+                format!("N{}", to)
+            }
+            Move::Memory { to } => {
+                self.open_migrate_dialog();
+                to.to_string()
+            }
+        };
+        if let Some(dialog) = &mut self.action_dialog {
+            dialog.input = input;
+            self.dialog_submit();
+        }
+    }
+
+    pub fn show_process_detail(&mut self) {
+        let Some(process) = self
+            .process_table_state
+            .selected()
+            .and_then(|i| self.visible_process_memory().get(i).copied())
+        else {
+            return;
+        };
+
+        let (pid, name) = (process.info.tgid, process.info.process_name.clone());
+        self.open_process_detail(pid, None, name, View::Processes);
+    }
+
+    // Detail of the selected popup task, or of the main thread for a process header row
+    pub fn show_popup_process_detail(&mut self) {
+        let Some((pid, tid, name)) = self.popup_state.table_state.selected().and_then(|i| {
+            self.popup_rows().get(i).map(|row| match row {
+                PopupRow::Process(threads) => {
+                    (threads[0].tgid, None, threads[0].process_name.clone())
+                }
+                PopupRow::Task(task) => (task.tgid, Some(task.tid), task.process_name.clone()),
+            })
+        }) else {
+            return;
+        };
+
+        self.open_process_detail(pid, tid, name, View::Nodes);
+    }
+
+    // `tid` None opens the main thread, standing in for the whole process
+    fn open_process_detail(&mut self, pid: u32, tid: Option<u32>, name: String, return_view: View) {
+        self.process_detail = ProcessDetail {
+            return_view,
+            pid,
+            tid: tid.unwrap_or(pid),
+            whole_process: tid.is_none(),
+            name,
+            ..Default::default()
+        };
+        self.update_process_detail();
+        self.view = View::ProcessDetail;
+    }
+
+    pub fn hide_process_detail(&mut self) {
+        self.view = self.process_detail.return_view;
+        if self.view == View::Processes {
+            self.update_process_memory(true);
+        }
+    }
+
+    // The lists as shown, after applying the '/' filter
+    pub fn visible_process_memory(&self) -> Vec<&ProcessNumaMemory> {
+        self.process_memory
+            .iter()
+            .filter(|p| {
+                self.filter
+                    .matches(p.info.filter_fields().iter().map(|f| f.as_str()))
+            })
+            .collect()
+    }
+
+    pub fn visible_popup_processes(&self) -> Vec<&ProcessInfo> {
+        self.popup_state
+            .processes
+            .iter()
+            .filter(|p| !(self.hide_kernel_threads && p.is_kernel_thread))
+            .filter(|p| {
+                self.filter
+                    .matches(p.filter_fields().iter().map(|f| f.as_str()))
+            })
+            .collect()
+    }
+
+    pub fn visible_suggestions(&self) -> Vec<&Suggestion> {
+        self.suggestions
+            .iter()
+            .filter(|s| {
+                self.filter
+                    .matches(s.filter_fields().iter().map(|f| f.as_str()))
+            })
+            .collect()
+    }
+
+    pub fn visible_migrations(&self) -> Vec<&ThreadMigrations> {
+        self.migration_tracker
+            .most_migrating()
+            .into_iter()
+            .filter(|t| !(self.hide_kernel_threads && t.is_kernel_thread))
+            .filter(|t| {
+                self.filter
+                    .matches(t.filter_fields().iter().map(|f| f.as_str()))
+            })
+            .collect()
+    }
+
+    pub fn toggle_kernel_threads(&mut self) {
+        self.hide_kernel_threads = !self.hide_kernel_threads;
+        self.restore_popup_selection();
+    }
+
+    pub fn start_search(&mut self) {
+        self.input_mode = InputMode::Search;
+    }
+
+    pub fn push_search_char(&mut self, c: char) {
+        let mut pattern = self.filter.pattern.clone();
+        pattern.push(c);
+        self.filter.set_pattern(pattern);
+        self.restore_popup_selection();
+    }
+
+    pub fn pop_search_char(&mut self) {
+        let mut pattern = self.filter.pattern.clone();
+        pattern.pop();
+        self.filter.set_pattern(pattern);
+        self.restore_popup_selection();
+    }
+
+    // Enter keeps the filter, ESC drops it
+    pub fn finish_search(&mut self, keep_filter: bool) {
+        if !keep_filter {
+            self.filter.clear();
+        }
+        self.input_mode = InputMode::Normal;
+        // Row indices refer to the filtered lists, start over from the top
+        self.process_table_state.select(Some(0));
+        self.migration_table_state.select(Some(0));
+        self.suggestion_table_state.select(Some(0));
+        self.popup_state.selection = None;
+        self.popup_state.table_state.select(Some(0));
+        self.restore_popup_selection();
+    }
+
+    pub fn select_next(&mut self) {
+        match self.view {
+            View::Processes => self.process_table_state.select_next(),
+            View::ProcessDetail => self.process_detail.table_state.select_next(),
+            View::Migrations => self.migration_table_state.select_next(),
+            View::Suggestions => self.suggestion_table_state.select_next(),
+            View::Nodes => self.move_popup_selection(1),
+            View::Chart => {}
+        }
+    }
+
+    pub fn select_previous(&mut self) {
+        match self.view {
+            View::Processes => self.process_table_state.select_previous(),
+            View::ProcessDetail => self.process_detail.table_state.select_previous(),
+            View::Migrations => self.migration_table_state.select_previous(),
+            View::Suggestions => self.suggestion_table_state.select_previous(),
+            View::Nodes => self.move_popup_selection(-1),
+            View::Chart => {}
+        }
+    }
+
+    // numastat only has counters since boot, so rates come from the change since the last tick
+    fn update_locality_rates(&mut self) {
+        let now = Instant::now();
+        let elapsed_secs = self
+            .prev_numastat_time
+            .map(|prev| now.duration_since(prev).as_secs_f64())
+            .unwrap_or(0.0);
+
+        let mut current_numastat = HashMap::new();
+        for node in &mut self.numa_nodes {
+            let Some(stat) = node.numastat else {
+                continue;
+            };
+            if let Some(prev) = self.prev_numastat.get(&node.id)
+                && elapsed_secs > 0.0
+            {
+                let local = stat.local_node.saturating_sub(prev.local_node);
+                let other = stat.other_node.saturating_sub(prev.other_node);
+                node.local_alloc_percent =
+                    (local + other > 0).then(|| local as f64 / (local + other) as f64 * 100.0);
+                node.remote_allocs_per_sec = other as f64 / elapsed_secs;
+            }
+            current_numastat.insert(node.id, stat);
+        }
+        self.prev_numastat = current_numastat;
+        self.prev_numastat_time = Some(now);
+    }
+
+    // Set from the command line, before the first sample is taken
+    pub fn set_history_retention(&mut self, retention: Duration) {
+        self.history_retention = retention;
+        self.history = MetricsHistory::new(history_samples(retention));
+        self.chart.window = self.history.capacity;
+    }
+
+    fn update_io_rates(&mut self) {
+        let samples = match get_io_device_samples() {
+            Ok(samples) => samples,
+            Err(e) => {
+                eprintln!("Error fetching I/O counters: {}", e);
+                return;
+            }
+        };
+
+        let now = Instant::now();
+        let elapsed_secs = self
+            .prev_io_sample_time
+            .map(|prev| now.duration_since(prev).as_secs_f64())
+            .unwrap_or(0.0);
+
+        // Devices without NUMA affinity can only be attributed when there is a single node
+        let single_node_id = if self.numa_nodes.len() == 1 {
+            Some(self.numa_nodes[0].id)
+        } else {
+            None
+        };
+
+        for node in &mut self.numa_nodes {
+            node.io_devices.clear();
+        }
+
+        let mut current_io_counters = HashMap::new();
+        for sample in samples {
+            let key = (sample.kind, sample.name.clone());
+            let (read_rate, write_rate) = match self.prev_io_counters.get(&key) {
+                Some(prev) if elapsed_secs > 0.0 => (
+                    sample.counters.read_bytes.saturating_sub(prev.read_bytes) as f64
+                        / elapsed_secs,
+                    sample.counters.write_bytes.saturating_sub(prev.write_bytes) as f64
+                        / elapsed_secs,
+                ),
+                // First tick for this device, no prev data, so 0 B/s
+                _ => (0.0, 0.0),
+            };
+
+            if let Some(node_id) = sample.node.or(single_node_id)
+                && let Some(node) = self.numa_nodes.iter_mut().find(|n| n.id == node_id)
+            {
+                node.io_devices.push(IoDevice {
+                    name: sample.name,
+                    kind: sample.kind,
+                    read_bytes_per_sec: read_rate,
+                    write_bytes_per_sec: write_rate,
+                });
+            }
+            current_io_counters.insert(key, sample.counters);
+        }
+
+        self.prev_io_counters = current_io_counters;
+        self.prev_io_sample_time = Some(now);
     }
 
     pub fn show_cpu_popup(&mut self, cpu_core_id: u32) {
+        self.show_popup(PopupTarget::Core(cpu_core_id));
+    }
+
+    pub fn show_node_popup(&mut self, node_id: u32) {
+        self.show_popup(PopupTarget::Node(node_id));
+    }
+
+    fn show_popup(&mut self, target: PopupTarget) {
         self.popup_state.show = true;
-        self.popup_state.cpu_core_id = cpu_core_id;
+        self.popup_state.target = target;
+        self.popup_state.changes.clear();
+        self.popup_state.process_memory_kb.clear();
+        self.popup_state.memory_sampled_at = None;
+        self.popup_state.selection = None;
+        self.popup_state.table_state = TableState::default().with_selected(Some(0));
+
+        self.popup_state.processes = self.fetch_popup_processes();
+        self.popup_state.sampled_at = Some(Instant::now());
+        self.update_popup_process_memory();
+        self.sort_popup_processes();
+    }
+
+    // Tasks that last ran on the target's CPUs, found through the migration tracker's scan
+    fn fetch_popup_processes(&mut self) -> Vec<ProcessInfo> {
+        let cpu_ids = match self.popup_state.target {
+            PopupTarget::Core(cpu_core_id) => vec![cpu_core_id],
+            PopupTarget::Node(node_id) => self
+                .numa_nodes
+                .iter()
+                .find(|n| n.id == node_id)
+                .and_then(|n| n.cpus.as_ref())
+                .map(|cpus| cpus.iter().map(|cpu| cpu.id).collect())
+                .unwrap_or_default(),
+        };
+        if !self.migration_tracker.is_current() {
+            self.update_migrations();
+        }
+
+        let mut processes: Vec<ProcessInfo> = self
+            .migration_tracker
+            .threads
+            .values()
+            .filter(|thread| cpu_ids.contains(&thread.cpu))
+            .filter_map(|thread| get_process_info(thread.tgid, thread.tid).ok())
+            .collect();
+        // Sort by PID in reverse order, keeping each process's threads together in TID order
+        processes.sort_by_key(|p| (std::cmp::Reverse(p.tgid), p.tid));
+        processes
+    }
+
+    // Node popups also show how much of each process's memory lives on the node. Reading
+    // numa_maps is expensive, so it follows the process table's refresh interval.
+    fn update_popup_process_memory(&mut self) {
+        if !matches!(self.popup_state.target, PopupTarget::Node(_)) {
+            return;
+        }
+        let is_stale = self
+            .popup_state
+            .memory_sampled_at
+            .is_none_or(|sampled| sampled.elapsed() >= PROCESS_TABLE_REFRESH_INTERVAL);
+        if !is_stale {
+            return;
+        }
 
-        // Fetch processes with affinity to this CPU core
-        match get_processes_currently_on_core(cpu_core_id) {
-            Ok(processes) => {
-                self.popup_state.processes = processes;
+        let mut process_memory_kb = HashMap::new();
+        for task in &self.popup_state.processes {
+            if !process_memory_kb.contains_key(&task.tgid)
+                && let Ok(mappings) = parse_numa_maps(task.tgid)
+            {
+                process_memory_kb.insert(task.tgid, get_node_memory_kb(&mappings));
             }
-            Err(e) => {
-                eprintln!("Error fetching processes for CPU {}: {}", cpu_core_id, e);
-                self.popup_state.processes.clear();
+        }
+        self.popup_state.process_memory_kb = process_memory_kb;
+        self.popup_state.memory_sampled_at = Some(Instant::now());
+    }
+
+    // Re-fetch the tasks on the popup's CPUs, diff them against the previous tick and
+    // turn the utime/stime deltas into CPU%
+    fn refresh_popup_processes(&mut self) {
+        let mut current = self.fetch_popup_processes();
+
+        let now = Instant::now();
+        let elapsed_ticks = self
+            .popup_state
+            .sampled_at
+            .map(|prev| now.duration_since(prev).as_secs_f64() * clock_ticks_per_sec())
+            .unwrap_or(0.0);
+
+        // Tasks that already departed last tick are dropped now
+        let changes = std::mem::take(&mut self.popup_state.changes);
+        let mut previous: HashMap<u32, ProcessInfo> =
+            std::mem::take(&mut self.popup_state.processes)
+                .into_iter()
+                .filter(|task| changes.get(&task.tid) != Some(&TaskChange::Departed))
+                .map(|task| (task.tid, task))
+                .collect();
+
+        for task in &mut current {
+            match previous.remove(&task.tid) {
+                Some(prev) => {
+                    let delta_ticks = task.cpu_time_ticks.saturating_sub(prev.cpu_time_ticks);
+                    task.cpu_percent = if elapsed_ticks > 0.0 {
+                        (delta_ticks as f64 / elapsed_ticks * 100.0).min(100.0)
+                    } else {
+                        0.0
+                    };
+                }
+                None => {
+                    // No previous sample for a newcomer, so 0% until the next tick
+                    self.popup_state
+                        .changes
+                        .insert(task.tid, TaskChange::Appeared);
+                }
+            }
+        }
+
+        // Whatever is left in `previous` is no longer on this core
+        for (tid, task) in previous {
+            self.popup_state.changes.insert(tid, TaskChange::Departed);
+            current.push(task);
+        }
+
+        self.popup_state.processes = current;
+        self.popup_state.sampled_at = Some(now);
+        self.update_popup_process_memory();
+        self.sort_popup_processes();
+    }
+
+    // Sort by the selected key. When grouping, each process's threads stay contiguous and
+    // whole processes are ordered by their first thread, or by the sum of their threads'
+    // CPU% when sorting by CPU.
+    fn sort_popup_processes(&mut self) {
+        let key = self.popup_state.sort_key;
+        let descending = self.popup_state.sort_descending;
+        let directed = |ordering: Ordering| {
+            if descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        };
+        let processes = &mut self.popup_state.processes;
+
+        processes.sort_by(|a, b| directed(key.compare(a, b).then(a.tid.cmp(&b.tid))));
+
+        if self.popup_state.group_by_process {
+            // Stable sorts, so threads keep their order within each process
+            if key == PopupSortKey::Cpu {
+                let mut process_cpu: HashMap<u32, f64> = HashMap::new();
+                for task in processes.iter() {
+                    *process_cpu.entry(task.tgid).or_insert(0.0) += task.cpu_percent;
+                }
+                processes.sort_by(|a, b| {
+                    directed(
+                        process_cpu[&a.tgid]
+                            .total_cmp(&process_cpu[&b.tgid])
+                            .then(a.tgid.cmp(&b.tgid)),
+                    )
+                });
+            } else {
+                let mut first_position: HashMap<u32, usize> = HashMap::new();
+                for (position, task) in processes.iter().enumerate() {
+                    first_position.entry(task.tgid).or_insert(position);
+                }
+                processes.sort_by_key(|task| first_position[&task.tgid]);
+            }
+        }
+
+        self.restore_popup_selection();
+    }
+
+    // 's': CPU, TID, name, then whichever optional columns are shown
+    pub fn cycle_popup_sort_key(&mut self) {
+        let mut keys = vec![PopupSortKey::Cpu, PopupSortKey::Tid, PopupSortKey::Name];
+        keys.extend(
+            self.popup_state
+                .columns
+                .iter()
+                .map(|c| PopupSortKey::Column(*c)),
+        );
+
+        let next = keys
+            .iter()
+            .position(|k| *k == self.popup_state.sort_key)
+            .map_or(0, |i| (i + 1) % keys.len());
+        self.popup_state.sort_key = keys[next];
+        self.popup_state.sort_descending = keys[next].descending_by_default();
+        self.sort_popup_processes();
+    }
+
+    // 'S'
+    pub fn reverse_popup_sort(&mut self) {
+        self.popup_state.sort_descending = !self.popup_state.sort_descending;
+        self.sort_popup_processes();
+    }
+
+    // Rows as shown in the popup, after filtering and grouping
+    pub fn popup_rows(&self) -> Vec<PopupRow<'_>> {
+        let processes = self.visible_popup_processes();
+        if !self.popup_state.group_by_process {
+            return processes.into_iter().map(PopupRow::Task).collect();
+        }
+
+        let mut rows = Vec::new();
+        for threads in processes.chunk_by(|a, b| a.tgid == b.tgid) {
+            rows.push(PopupRow::Process(threads.to_vec()));
+            rows.extend(threads.iter().map(|t| PopupRow::Task(t)));
+        }
+        rows
+    }
+
+    // Point the table selection back at the selected task after the rows changed,
+    // falling back to the same row index if it is gone
+    fn restore_popup_selection(&mut self) {
+        let (index, selection) = {
+            let rows = self.popup_rows();
+            let index = self
+                .popup_state
+                .selection
+                .and_then(|selection| rows.iter().position(|r| r.selection() == selection))
+                .or_else(|| {
+                    let selected = self.popup_state.table_state.selected()?;
+                    Some(selected.min(rows.len().checked_sub(1)?))
+                });
+            (index, index.map(|i| rows[i].selection()))
+        };
+        self.popup_state.table_state.select(index);
+        self.popup_state.selection = selection;
+    }
+
+    fn move_popup_selection(&mut self, delta: isize) {
+        if !self.popup_state.show {
+            return;
+        }
+        let (index, selection) = {
+            let rows = self.popup_rows();
+            let Some(last) = rows.len().checked_sub(1) else {
+                return;
+            };
+            let index = self
+                .popup_state
+                .table_state
+                .selected()
+                .map_or(0, |i| i.saturating_add_signed(delta).min(last));
+            (index, rows[index].selection())
+        };
+        self.popup_state.table_state.select(Some(index));
+        self.popup_state.selection = Some(selection);
+    }
+
+    // Map each CPU id to the NUMA node it belongs to
+    pub fn cpu_node_map(&self) -> HashMap<u32, u32> {
+        let mut cpu_nodes = HashMap::new();
+        for node in &self.numa_nodes {
+            if let Some(cpus) = &node.cpus {
+                for cpu in cpus {
+                    cpu_nodes.insert(cpu.id, node.id);
+                }
             }
         }
+        cpu_nodes
+    }
+
+    pub fn toggle_popup_grouping(&mut self) {
+        self.popup_state.group_by_process = !self.popup_state.group_by_process;
+        self.sort_popup_processes();
+    }
+
+    // `index` into PopupColumn::ALL, from the 1-8 keys
+    pub fn toggle_popup_column(&mut self, index: usize) {
+        let Some(column) = PopupColumn::ALL.get(index).copied() else {
+            return;
+        };
+        let columns = &mut self.popup_state.columns;
+        if let Some(position) = columns.iter().position(|c| *c == column) {
+            columns.remove(position);
+            // Can't keep sorting by a hidden column
+            if self.popup_state.sort_key == PopupSortKey::Column(column) {
+                self.popup_state.sort_key = PopupSortKey::Cpu;
+                self.popup_state.sort_descending = true;
+                self.sort_popup_processes();
+            }
+        } else {
+            columns.push(column);
+            columns.sort();
+        }
     }
 
     pub fn hide_popup(&mut self) {
         self.popup_state.show = false;
         self.popup_state.processes.clear();
+        self.popup_state.changes.clear();
+    }
+
+    // The task under the cursor in the current view, for the process actions
+    pub fn selected_action_target(&self) -> Option<ActionTarget> {
+        match self.view {
+            View::Nodes if self.popup_state.show => {
+                let index = self.popup_state.table_state.selected()?;
+                match self.popup_rows().get(index)? {
+                    PopupRow::Process(threads) => Some(ActionTarget {
+                        pid: threads[0].tgid,
+                        tid: None,
+                        name: threads[0].process_name.clone(),
+                    }),
+                    PopupRow::Task(task) => Some(ActionTarget {
+                        pid: task.tgid,
+                        tid: Some(task.tid),
+                        name: task.thread_name.clone(),
+                    }),
+                }
+            }
+            View::Nodes | View::Chart => None,
+            View::Processes => {
+                let index = self.process_table_state.selected()?;
+                let process = self.visible_process_memory().get(index).copied()?;
+                Some(ActionTarget {
+                    pid: process.info.tgid,
+                    tid: None,
+                    name: process.info.process_name.clone(),
+                })
+            }
+            View::ProcessDetail => {
+                let detail = &self.process_detail;
+                Some(ActionTarget {
+                    pid: detail.pid,
+                    tid: (!detail.whole_process).then_some(detail.tid),
+                    name: detail.name.clone(),
+                })
+            }
+            View::Migrations => {
+                let index = self.migration_table_state.selected()?;
+                let thread = self.visible_migrations().get(index).copied()?;
+                Some(ActionTarget {
+                    pid: thread.tgid,
+                    tid: Some(thread.tid),
+                    name: thread.thread_name.clone(),
+                })
+            }
+            View::Suggestions => {
+                let index = self.suggestion_table_state.selected()?;
+                let suggestion = self.visible_suggestions().get(index).copied()?;
+                Some(ActionTarget {
+                    pid: suggestion.pid,
+                    tid: None,
+                    name: suggestion.name.clone(),
+                })
+            }
+        }
+    }
+
+    // "0-7 (N0 N1)"
+    fn describe_cpus(&self, cpus: &[u32]) -> String {
+        let cpu_nodes = self.cpu_node_map();
+        let mut nodes: Vec<u32> = cpus
+            .iter()
+            .filter_map(|cpu| cpu_nodes.get(cpu).copied())
+            .collect();
+        nodes.sort_unstable();
+        nodes.dedup();
+        let nodes: Vec<String> = nodes.iter().map(|id| format!("N{}", id)).collect();
+        format!("{} ({})", format_cpulist(cpus), nodes.join(" "))
+    }
+
+    // A core ("3"), a whole node ("N1") or a cpulist ("0-3,8")
+    fn parse_cpu_selection(&self, input: &str) -> Result<Vec<u32>, String> {
+        let input = input.trim();
+        let cpu_nodes = self.cpu_node_map();
+        let cpus = if let Some(node_str) = input.strip_prefix(['N', 'n']) {
+            let node_id: u32 = node_str
+                .parse()
+                .map_err(|_| format!("invalid node \"{}\"", input))?;
+            let node = self
+                .numa_nodes
+                .iter()
+                .find(|n| n.id == node_id)
+                .ok_or_else(|| format!("no NUMA node {}", node_id))?;
+            node.cpus
+                .iter()
+                .flatten()
+                .map(|cpu| cpu.id)
+                .collect::<Vec<u32>>()
+        } else {
+            let last_cpu = cpu_nodes.keys().max().copied().unwrap_or_default();
+            parse_cpulist_bounded(input, last_cpu)
+                .map_err(|e| format!("invalid cpulist \"{}\": {}", input, e))?
+        };
+
+        if cpus.is_empty() {
+            return Err("no CPUs selected".to_string());
+        }
+        if let Some(cpu) = cpus.iter().find(|cpu| !cpu_nodes.contains_key(cpu)) {
+            return Err(format!("CPU {} is not present", cpu));
+        }
+        Ok(cpus)
+    }
+
+    // "N0 1228.8 MiB  N1 30.0 MiB"
+    fn describe_node_memory(node_memory_kb: &BTreeMap<u32, u64>) -> String {
+        node_memory_kb
+            .iter()
+            .map(|(node_id, kb)| format!("N{} {:.1} MiB", node_id, *kb as f64 / 1024.0))
+            .collect::<Vec<_>>()
+            .join("  ")
+    }
+
+    fn process_node_memory_kb(pid: u32) -> io::Result<BTreeMap<u32, u64>> {
+        Ok(get_node_memory_kb(&parse_numa_maps(pid)?))
+    }
+
+    // A destination node ("1", from every other node) or source and destination node
+    // lists ("0 1", "0,2 1"), like migratepages(8)
+    fn parse_migration_nodes(
+        input: &str,
+        node_ids: &[u32],
+    ) -> Result<(Vec<u32>, Vec<u32>), String> {
+        let parse_nodes = |list: &str| -> Result<Vec<u32>, String> {
+            let list = list.trim_start_matches(['N', 'n']);
+            let last_node = node_ids.iter().max().copied().unwrap_or_default();
+            let nodes = parse_cpulist_bounded(list, last_node)
+                .map_err(|e| format!("invalid node list \"{}\": {}", list, e))?;
+            if let Some(node) = nodes.iter().find(|node| !node_ids.contains(node)) {
+                return Err(format!("no NUMA node {}", node));
+            }
+            Ok(nodes)
+        };
+
+        let parts: Vec<&str> = input.split_whitespace().collect();
+        let (from, to) = match parts.as_slice() {
+            [to] => {
+                let to = parse_nodes(to)?;
+                let from = node_ids
+                    .iter()
+                    .copied()
+                    .filter(|id| !to.contains(id))
+                    .collect();
+                (from, to)
+            }
+            [from, to] => (parse_nodes(from)?, parse_nodes(to)?),
+            _ => return Err("expected a node, or source and destination nodes".to_string()),
+        };
+        if from.is_empty() || from == to {
+            return Err("nothing to move, source and destination are the same".to_string());
+        }
+        Ok((from, to))
+    }
+
+    fn parse_memory_policy(&self, input: &str) -> Result<MemoryPolicy, String> {
+        let policy = MemoryPolicy::parse(input)?;
+        if let Some(node) = policy
+            .nodes()
+            .iter()
+            .find(|node| !self.numa_nodes.iter().any(|n| n.id == **node))
+        {
+            return Err(format!("no NUMA node {}", node));
+        }
+        Ok(policy)
+    }
+
+    fn open_dialog(&mut self, kind: ActionKind, target: Option<ActionTarget>, before: String) {
+        if self.read_only {
+            self.set_status(
+                "Read-only mode: actions that change the system are disabled".to_string(),
+                true,
+            );
+            return;
+        }
+        self.action_dialog = Some(ActionDialog {
+            kind,
+            target,
+            stage: DialogStage::Input,
+            input: String::new(),
+            error: None,
+            before,
+            after: String::new(),
+            command: String::new(),
+            pending: None,
+        });
+    }
+
+    pub fn open_affinity_dialog(&mut self) {
+        let Some(target) = self.selected_action_target() else {
+            return;
+        };
+        let before = match get_process_info(target.pid, target.tid.unwrap_or(target.pid)) {
+            Ok(task) => self.describe_cpus(&task.cpus_allowed),
+            Err(e) => {
+                self.set_status(format!("{}: {}", target.describe(), e), true);
+                return;
+            }
+        };
+        self.open_dialog(ActionKind::Affinity, Some(target), before);
+    }
+
+    // Memory belongs to the process, so this always acts on the whole process
+    pub fn open_migrate_dialog(&mut self) {
+        let Some(target) = self.selected_action_target().map(ActionTarget::process) else {
+            return;
+        };
+        if let Some(migration) = &self.memory_migration {
+            self.set_status(
+                format!(
+                    "Already migrating the memory of {}",
+                    migration.target.describe()
+                ),
+                true,
+            );
+            return;
+        }
+
+        let before = match Self::process_node_memory_kb(target.pid) {
+            Ok(node_memory_kb) => Self::describe_node_memory(&node_memory_kb),
+            Err(e) => {
+                self.set_status(format!("{}: {}", target.describe(), e), true);
+                return;
+            }
+        };
+        self.open_dialog(ActionKind::MigrateMemory, Some(target), before);
+    }
+
+    // Like memory migration this always acts on the whole process
+    pub fn open_policy_dialog(&mut self) {
+        let Some(target) = self.selected_action_target().map(ActionTarget::process) else {
+            return;
+        };
+        if let Some(relaunch) = &self.policy_relaunch {
+            self.set_status(
+                format!("Already relaunching {}", relaunch.target.describe()),
+                true,
+            );
+            return;
+        }
+
+        let before = match parse_numa_maps(target.pid) {
+            Ok(mappings) => dominant_policy(&mappings).unwrap_or("default").to_string(),
+            Err(e) => {
+                self.set_status(format!("{}: {}", target.describe(), e), true);
+                return;
+            }
+        };
+        self.open_dialog(ActionKind::MemoryPolicy, Some(target), before);
+    }
+
+    // Signals go to the whole process, like `kill` sends them
+    pub fn open_signal_dialog(&mut self) {
+        let Some(target) = self.selected_action_target().map(ActionTarget::process) else {
+            return;
+        };
+        let before = match get_process_info(target.pid, target.pid) {
+            Ok(task) => format!("{} ({})", task.state, describe_task_state(task.state)),
+            Err(e) => {
+                self.set_status(format!("{}: {}", target.describe(), e), true);
+                return;
+            }
+        };
+        self.open_dialog(ActionKind::Signal, Some(target), before);
+    }
+
+    pub fn open_priority_dialog(&mut self) {
+        let Some(target) = self.selected_action_target() else {
+            return;
+        };
+        let tid = target.tid.unwrap_or(target.pid);
+        let before = match get_process_info(target.pid, tid) {
+            Ok(task) => {
+                let io = get_io_priority(tid)
+                    .map(|io| io.describe())
+                    .unwrap_or_else(|_| "?".to_string());
+                format!("nice {}, io {}", task.nice, io)
+            }
+            Err(e) => {
+                self.set_status(format!("{}: {}", target.describe(), e), true);
+                return;
+            }
+        };
+        self.open_dialog(ActionKind::Priority, Some(target), before);
+    }
+
+    // The node the run prompt binds to: the open node popup, the node of the open core
+    // popup, or the node cursor
+    pub fn cursor_node(&self) -> Option<u32> {
+        if self.popup_state.show {
+            return match self.popup_state.target {
+                PopupTarget::Node(node_id) => Some(node_id),
+                PopupTarget::Core(cpu_id) => self.cpu_node_map().get(&cpu_id).copied(),
+            };
+        }
+        self.numa_nodes
+            .iter()
+            .any(|n| n.id == self.selected_node)
+            .then_some(self.selected_node)
+    }
+
+    pub fn select_adjacent_node(&mut self, forward: bool) {
+        let Some(index) = self
+            .numa_nodes
+            .iter()
+            .position(|n| n.id == self.selected_node)
+        else {
+            if let Some(node) = self.numa_nodes.first() {
+                self.selected_node = node.id;
+            }
+            return;
+        };
+        let index = if forward {
+            (index + 1).min(self.numa_nodes.len() - 1)
+        } else {
+            index.saturating_sub(1)
+        };
+        self.selected_node = self.numa_nodes[index].id;
+    }
+
+    fn node_cpus(&self, node_id: u32) -> Vec<u32> {
+        self.numa_nodes
+            .iter()
+            .filter(|n| n.id == node_id)
+            .flat_map(|n| n.cpus.iter().flatten())
+            .map(|cpu| cpu.id)
+            .collect()
+    }
+
+    pub fn open_run_dialog(&mut self) {
+        let Some(node_id) = self.cursor_node() else {
+            return;
+        };
+        let cpus = self.node_cpus(node_id);
+        if cpus.is_empty() {
+            self.set_status(
+                format!("N{} has no CPUs to run a command on", node_id),
+                true,
+            );
+            return;
+        }
+        let before = format!("N{} (CPUs {})", node_id, format_cpulist(&cpus));
+        self.open_dialog(ActionKind::Run(node_id), None, before);
+    }
+
+    pub fn dialog_push_char(&mut self, c: char) {
+        if let Some(dialog) = &mut self.action_dialog
+            && dialog.stage == DialogStage::Input
+        {
+            dialog.input.push(c);
+            dialog.error = None;
+        }
+    }
+
+    pub fn dialog_pop_char(&mut self) {
+        if let Some(dialog) = &mut self.action_dialog
+            && dialog.stage == DialogStage::Input
+        {
+            dialog.input.pop();
+            dialog.error = None;
+        }
+    }
+
+    // Enter: validate the input and move on to the confirmation
+    pub fn dialog_submit(&mut self) {
+        let Some(dialog) = &self.action_dialog else {
+            return;
+        };
+        if dialog.stage != DialogStage::Input {
+            return;
+        }
+
+        let parsed = match dialog.kind {
+            ActionKind::Affinity => self
+                .parse_cpu_selection(&dialog.input)
+                .map(|cpus| (self.describe_cpus(&cpus), PendingAction::SetAffinity(cpus))),
+            ActionKind::MigrateMemory => {
+                let node_ids: Vec<u32> = self.numa_nodes.iter().map(|n| n.id).collect();
+                Self::parse_migration_nodes(&dialog.input, &node_ids).map(|(from, to)| {
+                    let to_move_kb: u64 = dialog
+                        .target
+                        .as_ref()
+                        .and_then(|target| Self::process_node_memory_kb(target.pid).ok())
+                        .map(|memory| from.iter().filter_map(|n| memory.get(n)).sum())
+                        .unwrap_or(0);
+                    (
+                        format!(
+                            "N{} → N{} ({:.1} MiB to move)",
+                            format_cpulist(&from),
+                            format_cpulist(&to),
+                            to_move_kb as f64 / 1024.0
+                        ),
+                        PendingAction::MigrateMemory { from, to },
+                    )
+                })
+            }
+            ActionKind::MemoryPolicy => self.parse_memory_policy(&dialog.input).map(|policy| {
+                (
+                    format!("{} (restarts the process)", policy.describe()),
+                    PendingAction::RelaunchWithPolicy(policy),
+                )
+            }),
+            ActionKind::Signal => Signal::parse(&dialog.input)
+                .map(|signal| (signal.name().to_string(), PendingAction::Signal(signal))),
+            ActionKind::Priority => PriorityChange::parse(&dialog.input)
+                .map(|change| (change.describe(), PendingAction::SetPriority(change))),
+            ActionKind::Run(node) => {
+                let command = dialog.input.trim().to_string();
+                if command.is_empty() {
+                    Err("type the command to run".to_string())
+                } else {
+                    Ok((
+                        format!("{} with CPUs and memory bound to N{}", command, node),
+                        PendingAction::Run { command, node },
+                    ))
+                }
+            }
+        };
+
+        let parsed = parsed.map(|(after, pending)| {
+            let command = self.equivalent_command(dialog.target.as_ref(), &pending);
+            (after, command, pending)
+        });
+        let Some(dialog) = &mut self.action_dialog else {
+            return;
+        };
+        match parsed {
+            Ok((after, command, pending)) => {
+                dialog.after = after;
+                dialog.command = command;
+                dialog.pending = Some(pending);
+                dialog.stage = DialogStage::Confirm;
+            }
+            Err(error) => dialog.error = Some(error),
+        }
+    }
+
+    // y applies the pending action, anything else goes back without changes
+    pub fn dialog_confirm(&mut self, apply: bool) {
+        let Some(dialog) = self.action_dialog.take() else {
+            return;
+        };
+        let Some(pending) = dialog.pending.filter(|_| apply) else {
+            self.set_status("Cancelled".to_string(), false);
+            return;
+        };
+
+        let target_description = dialog
+            .target
+            .as_ref()
+            .map(|target| target.describe())
+            .unwrap_or_else(|| "a new process".to_string());
+        let command = dialog.command;
+        // Dialogs don't open in read-only mode, this only guards against that changing
+        if self.read_only {
+            self.set_status(
+                "Read-only mode: actions that change the system are disabled".to_string(),
+                true,
+            );
+            return;
+        }
+        if self.dry_run {
+            self.report_action(
+                AuditOutcome::DryRun,
+                &target_description,
+                &command,
+                format!("Dry run, not applied: {}", command),
+            );
+            return;
+        }
+        // Fail closed: an action that can't be put on record first isn't applied
+        if let Some(audit_log) = &self.audit_log
+            && let Err(e) = audit_log.record(
+                AuditOutcome::Attempt,
+                &target_description,
+                &command,
+                &dialog.after,
+            )
+        {
+            let text = format!(
+                "Not applied, the audit log {} can't be written: {}",
+                audit_log.path().display(),
+                e
+            );
+            self.set_status(text, true);
+            return;
+        }
+
+        match (pending, dialog.target) {
+            (PendingAction::SetAffinity(cpus), Some(target)) => {
+                match set_target_cpu_affinity(&target, &cpus) {
+                    Ok(threads) => self.report_action(
+                        AuditOutcome::Ok,
+                        &target_description,
+                        &command,
+                        format!(
+                            "Pinned {} to CPUs {} ({} thread{})",
+                            target_description,
+                            dialog.after,
+                            threads,
+                            if threads == 1 { "" } else { "s" }
+                        ),
+                    ),
+                    Err(e) => self.report_action(
+                        AuditOutcome::Failed,
+                        &target_description,
+                        &command,
+                        format!(
+                            "Failed to set the CPU affinity of {}: {}",
+                            target_description,
+                            describe_action_error(&e)
+                        ),
+                    ),
+                }
+            }
+            (PendingAction::MigrateMemory { from, to }, Some(target)) => {
+                self.start_memory_migration(target, from, to, command);
+            }
+            (PendingAction::RelaunchWithPolicy(policy), Some(target)) => {
+                self.set_status(
+                    format!("Stopping {} to relaunch it", target_description),
+                    false,
+                );
+                let (pid, thread_policy) = (target.pid, policy.clone());
+                self.policy_relaunch = Some(PolicyRelaunch {
+                    target,
+                    policy,
+                    command,
+                    // Waiting for the process to exit takes a while, keep the UI responsive
+                    handle: thread::spawn(move || relaunch_with_policy(pid, &thread_policy)),
+                });
+            }
+            (PendingAction::Signal(signal), Some(target)) => match send_signal(&target, signal) {
+                Ok(()) => self.report_action(
+                    AuditOutcome::Ok,
+                    &target_description,
+                    &command,
+                    format!("Sent {} to {}", signal.name(), target_description),
+                ),
+                Err(e) => self.report_action(
+                    AuditOutcome::Failed,
+                    &target_description,
+                    &command,
+                    format!(
+                        "Failed to send {} to {}: {}",
+                        signal.name(),
+                        target_description,
+                        describe_action_error(&e)
+                    ),
+                ),
+            },
+            (PendingAction::SetPriority(change), Some(target)) => {
+                match set_target_priority(&target, &change) {
+                    Ok(threads) => self.report_action(
+                        AuditOutcome::Ok,
+                        &target_description,
+                        &command,
+                        format!(
+                            "Set {} on {} ({} thread{})",
+                            change.describe(),
+                            target_description,
+                            threads,
+                            if threads == 1 { "" } else { "s" }
+                        ),
+                    ),
+                    Err(e) => self.report_action(
+                        AuditOutcome::Failed,
+                        &target_description,
+                        &command,
+                        format!(
+                            "Failed to change the priority of {}: {}",
+                            target_description,
+                            describe_action_error(&e)
+                        ),
+                    ),
+                }
+            }
+            (PendingAction::Run { command: run, node }, _) => {
+                self.launch_command(run, node, &command)
+            }
+            // Task actions always come with a target
+            (_, None) => {}
+        }
+
+        // Show the outcome right away
+        if self.view == View::Processes {
+            self.update_process_memory(true);
+        }
+        if self.view == View::Suggestions {
+            self.update_process_memory(true);
+            self.update_suggestions();
+        }
+        if self.popup_state.show {
+            self.refresh_popup_processes();
+        }
+        if self.view == View::ProcessDetail {
+            self.update_process_detail();
+        }
+    }
+
+    // migrate_pages blocks until every page has moved, so run it on its own thread
+    fn start_memory_migration(
+        &mut self,
+        target: ActionTarget,
+        from: Vec<u32>,
+        to: Vec<u32>,
+        command: String,
+    ) {
+        let initial_kb = Self::process_node_memory_kb(target.pid)
+            .map(|memory| from.iter().filter_map(|n| memory.get(n)).sum())
+            .unwrap_or(0);
+
+        let (pid, thread_from, thread_to) = (target.pid, from.clone(), to.clone());
+        let handle = thread::spawn(move || migrate_process_pages(pid, &thread_from, &thread_to));
+
+        self.set_status(
+            format!(
+                "Migrating the memory of {} from N{} to N{}",
+                target.describe(),
+                format_cpulist(&from),
+                format_cpulist(&to)
+            ),
+            false,
+        );
+        self.memory_migration = Some(MemoryMigration {
+            target,
+            from,
+            to,
+            initial_kb,
+            remaining_kb: initial_kb,
+            started: Instant::now(),
+            command,
+            handle,
+        });
+    }
+
+    // Follow the running migration through numa_maps and report once it has finished
+    fn update_memory_migration(&mut self) {
+        let Some(migration) = &mut self.memory_migration else {
+            return;
+        };
+        if let Ok(memory) = Self::process_node_memory_kb(migration.target.pid) {
+            migration.remaining_kb = migration.from.iter().filter_map(|n| memory.get(n)).sum();
+        }
+        if !migration.handle.is_finished() {
+            return;
+        }
+
+        let Some(migration) = self.memory_migration.take() else {
+            return;
+        };
+        let moved_kb = migration.initial_kb.saturating_sub(migration.remaining_kb);
+        let (text, is_error) = match migration.handle.join() {
+            Ok(Ok(not_moved)) => (
+                format!(
+                    "Moved {:.1} MiB of {} from N{} to N{} in {:.1}s{}",
+                    moved_kb as f64 / 1024.0,
+                    migration.target.describe(),
+                    format_cpulist(&migration.from),
+                    format_cpulist(&migration.to),
+                    migration.started.elapsed().as_secs_f64(),
+                    if not_moved > 0 {
+                        format!(", {} pages could not be moved", not_moved)
+                    } else {
+                        String::new()
+                    }
+                ),
+                false,
+            ),
+            Ok(Err(e)) => (
+                format!(
+                    "Failed to migrate the memory of {}: {}",
+                    migration.target.describe(),
+                    describe_action_error(&e)
+                ),
+                true,
+            ),
+            Err(_) => (
+                format!(
+                    "Memory migration of {} panicked",
+                    migration.target.describe()
+                ),
+                true,
+            ),
+        };
+        let outcome = if is_error {
+            AuditOutcome::Failed
+        } else {
+            AuditOutcome::Ok
+        };
+        self.report_action(
+            outcome,
+            &migration.target.describe(),
+            &migration.command,
+            text,
+        );
+    }
+
+    // Like `numactl --cpunodebind --membind`
+    fn launch_command(&mut self, command: String, node: u32, equivalent: &str) {
+        let spec = LaunchSpec {
+            cpus: Some(self.node_cpus(node)),
+            ..LaunchSpec::from_command_line(&command)
+        };
+        match spawn_with_placement(&spec, Some(&MemoryPolicy::Bind(vec![node]))) {
+            Ok(pid) => {
+                self.report_action(
+                    AuditOutcome::Ok,
+                    &format!("PID {} ({})", pid, command),
+                    equivalent,
+                    format!("Started PID {} on N{}: {}", pid, node, command),
+                );
+                self.launched.push(LaunchedProcess {
+                    pid,
+                    command,
+                    node,
+                    info: None,
+                    node_memory_kb: BTreeMap::new(),
+                    exited: false,
+                });
+                if self.launched.len() > MAX_LAUNCHED_PROCESSES {
+                    self.launched.remove(0);
+                }
+                self.update_launched_processes();
+            }
+            Err(e) => self.report_action(
+                AuditOutcome::Failed,
+                "a new process",
+                equivalent,
+                format!("Failed to run {}: {}", command, describe_action_error(&e)),
+            ),
+        }
+    }
+
+    // Where the launched commands run and where their memory ended up
+    fn update_launched_processes(&mut self) {
+        for launched in self.launched.iter_mut().filter(|l| !l.exited) {
+            match get_process_info(launched.pid, launched.pid) {
+                Ok(info) => {
+                    launched.info = Some(info);
+                    if let Ok(memory) = Self::process_node_memory_kb(launched.pid) {
+                        launched.node_memory_kb = memory;
+                    }
+                }
+                Err(_) => launched.exited = true,
+            }
+        }
+    }
+
+    fn update_policy_relaunch(&mut self) {
+        if !self
+            .policy_relaunch
+            .as_ref()
+            .is_some_and(|relaunch| relaunch.handle.is_finished())
+        {
+            return;
+        }
+        let Some(relaunch) = self.policy_relaunch.take() else {
+            return;
+        };
+
+        let target = relaunch.target.describe();
+        let result = relaunch.handle.join();
+        let (text, is_error) = match &result {
+            Ok(Ok(new_pid)) => (
+                format!(
+                    "Relaunched {} as PID {} under {}",
+                    target,
+                    new_pid,
+                    relaunch.policy.describe()
+                ),
+                false,
+            ),
+            Ok(Err(e)) => (
+                format!(
+                    "Failed to relaunch {}: {}",
+                    target,
+                    describe_action_error(e)
+                ),
+                true,
+            ),
+            Err(_) => (format!("Relaunching {} panicked", target), true),
+        };
+        let outcome = if is_error {
+            AuditOutcome::Failed
+        } else {
+            AuditOutcome::Ok
+        };
+        self.report_action(outcome, &target, &relaunch.command, text);
+
+        // Follow the process to its new PID instead of showing the old one as exited
+        if let Ok(Ok(new_pid)) = result
+            && self.view == View::ProcessDetail
+            && self.process_detail.pid == relaunch.target.pid
+        {
+            let name = self.process_detail.name.clone();
+            let return_view = self.process_detail.return_view;
+            self.open_process_detail(new_pid, None, name, return_view);
+        }
+    }
+
+    // The shell command doing what the pending action does, for dry runs and the audit log
+    fn equivalent_command(&self, target: Option<&ActionTarget>, pending: &PendingAction) -> String {
+        // renice and ionice take one TID at a time, like our syscalls
+        let tids = |target: &ActionTarget| {
+            target
+                .tids()
+                .unwrap_or_else(|_| vec![target.tid.unwrap_or(target.pid)])
+                .iter()
+                .map(u32::to_string)
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        match (pending, target) {
+            (PendingAction::SetAffinity(cpus), Some(target)) => match target.tid {
+                Some(tid) => format!("taskset -p -c {} {}", format_cpulist(cpus), tid),
+                None => format!("taskset -a -p -c {} {}", format_cpulist(cpus), target.pid),
+            },
+            (PendingAction::MigrateMemory { from, to }, Some(target)) => format!(
+                "migratepages {} {} {}",
+                target.pid,
+                format_cpulist(from),
+                format_cpulist(to)
+            ),
+            (PendingAction::RelaunchWithPolicy(policy), Some(target)) => format!(
+                "kill -s TERM {} && {}",
+                target.pid,
+                LaunchSpec::from_process(target.pid)
+                    .map(|spec| spec.numactl_command(policy))
+                    .unwrap_or_else(|_| format!("numactl {} -- ?", policy.numactl_option()))
+            ),
+            (PendingAction::Signal(signal), Some(target)) => {
+                format!("kill -s {} {}", signal.short_name(), target.pid)
+            }
+            (PendingAction::SetPriority(change), Some(target)) => {
+                let tids = tids(target);
+                let mut commands = Vec::new();
+                if let Some(nice) = change.nice {
+                    commands.push(format!("renice -n {} -p {}", nice, tids));
+                }
+                if let Some(io) = change.io {
+                    commands.push(format!("ionice {} -p {}", io.ionice_options(), tids));
+                }
+                commands.join(" && ")
+            }
+            (PendingAction::Run { command, node }, _) => format!(
+                "numactl --cpunodebind={} --membind={} -- {}",
+                node,
+                node,
+                LaunchSpec::from_command_line(command).shell_command()
+            ),
+            (_, None) => String::new(),
+        }
+    }
+
+    // Show the outcome of a confirmed action and append it to the audit log
+    fn report_action(&mut self, outcome: AuditOutcome, target: &str, command: &str, text: String) {
+        let mut is_error = outcome == AuditOutcome::Failed;
+        let mut text = text;
+        if let Some(audit_log) = &self.audit_log
+            && let Err(e) = audit_log.record(outcome, target, command, &text)
+        {
+            text = format!(
+                "{} (not written to {}: {})",
+                text,
+                audit_log.path().display(),
+                e
+            );
+            is_error = true;
+        }
+        self.set_status(text, is_error);
+    }
+
+    pub fn toggle_dry_run(&mut self) {
+        self.dry_run = !self.dry_run;
+        let text = if self.dry_run {
+            "Dry run on: confirmed actions are logged with their command, not applied"
+        } else {
+            "Dry run off: confirmed actions are applied"
+        };
+        self.set_status(text.to_string(), false);
+    }
+
+    // The read-only feature can't be turned off from the command line
+    pub fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only || READ_ONLY_BUILD;
+    }
+
+    pub fn set_audit_log_path(&mut self, path: Option<PathBuf>) {
+        self.audit_log = path.map(AuditLog::new);
+    }
+
+    pub fn close_dialog(&mut self) {
+        self.action_dialog = None;
+    }
+
+    fn set_status(&mut self, text: String, is_error: bool) {
+        self.status = Some(StatusMessage {
+            text,
+            is_error,
+            shown_at: Instant::now(),
+        });
     }
 
     pub fn handle_mouse_click(&mut self, x: u16, y: u16) {
@@ -118,17 +2215,107 @@ impl App {
                 return;
             }
         }
+
+        // Then the node titles and memory gauges
+        for node_area in &self.node_areas {
+            if x >= node_area.area.x
+                && x < node_area.area.x + node_area.area.width
+                && y >= node_area.area.y
+                && y < node_area.area.y + node_area.area.height
+            {
+                self.show_node_popup(node_area.node_id);
+                return;
+            }
+        }
     }
 
-    pub fn clear_cpu_core_areas(&mut self) {
+    pub fn clear_click_areas(&mut self) {
         self.cpu_core_areas.clear();
+        self.node_areas.clear();
+    }
+
+    pub fn add_node_area(&mut self, node_id: u32, area: Rect) {
+        self.node_areas.push(NodeArea { node_id, area });
     }
 
     pub fn add_cpu_core_area(&mut self, cpu_id: u32, area: Rect) {
         self.cpu_core_areas.push(CpuCoreArea { cpu_id, area });
     }
 
+    // Quitting halfway through a relaunch would leave the process stopped and never started
+    // again, so wait for background actions to finish and be logged first
     pub fn exit(&mut self) {
-        self.should_exit = true;
+        self.exit_requested = true;
+        let pending = match (&self.memory_migration, &self.policy_relaunch) {
+            (Some(migration), _) => {
+                format!("the memory migration of {}", migration.target.describe())
+            }
+            (_, Some(relaunch)) => format!("the relaunch of {}", relaunch.target.describe()),
+            (None, None) => {
+                self.should_exit = true;
+                return;
+            }
+        };
+        self.set_status(format!("Quitting once {} finishes", pending), false);
+    }
+}
+
+// One sample is taken per tick
+fn history_samples(retention: Duration) -> usize {
+    (retention.as_millis() / crate::TICK_RATE.as_millis()).max(1) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_migration_nodes_to_a_single_node_moves_every_other_one() {
+        assert_eq!(
+            App::parse_migration_nodes("1", &[0, 1, 2]),
+            Ok((vec![0, 2], vec![1]))
+        );
+        assert_eq!(
+            App::parse_migration_nodes("N1", &[0, 1, 2]),
+            Ok((vec![0, 2], vec![1]))
+        );
+    }
+
+    #[test]
+    fn parse_migration_nodes_takes_source_and_destination_lists() {
+        assert_eq!(
+            App::parse_migration_nodes("0 1", &[0, 1]),
+            Ok((vec![0], vec![1]))
+        );
+        assert_eq!(
+            App::parse_migration_nodes("N0,2 n1", &[0, 1, 2]),
+            Ok((vec![0, 2], vec![1]))
+        );
+        assert_eq!(
+            App::parse_migration_nodes("0-1 N3", &[0, 1, 2, 3]),
+            Ok((vec![0, 1], vec![3]))
+        );
+    }
+
+    #[test]
+    fn parse_migration_nodes_rejects_bad_input() {
+        let nodes = [0, 1];
+        for input in [
+            "",
+            "N",
+            "0 1 0",
+            "2",
+            "0 2",
+            "0-4000000000 1",
+            "x",
+            "0 0",
+            "0,1",
+        ] {
+            assert!(
+                App::parse_migration_nodes(input, &nodes).is_err(),
+                "{:?}",
+                input
+            );
+        }
     }
 }
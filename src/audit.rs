@@ -0,0 +1,120 @@
+use crate::proc_info::user_name;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// How an action ended, the third field of every audit line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditOutcome {
+    Attempt, // Written before an action is applied, an ok or failed line follows
+    Ok,
+    Failed,
+    DryRun, // Confirmed while dry-run was on, nothing was changed
+}
+
+impl AuditOutcome {
+    fn as_str(&self) -> &'static str {
+        match self {
+            AuditOutcome::Attempt => "attempt",
+            AuditOutcome::Ok => "ok",
+            AuditOutcome::Failed => "failed",
+            AuditOutcome::DryRun => "dry-run",
+        }
+    }
+}
+
+// Append-only, tab-separated record of every confirmed action:
+// time, user, outcome, target, equivalent command, result message
+#[derive(Debug, Clone)]
+pub struct AuditLog {
+    path: PathBuf,
+}
+
+impl AuditLog {
+    pub fn new(path: PathBuf) -> AuditLog {
+        AuditLog { path }
+    }
+
+    // $XDG_STATE_HOME/numatop/audit.log, falling back to ~/.local/state
+    pub fn default_path() -> Option<PathBuf> {
+        let state_home = std::env::var_os("XDG_STATE_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| {
+                std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/state"))
+            })?;
+        Some(state_home.join("numatop").join("audit.log"))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn record(
+        &self,
+        outcome: AuditOutcome,
+        target: &str,
+        command: &str,
+        result: &str,
+    ) -> io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let line = [
+            timestamp(),
+            acting_user(),
+            outcome.as_str().to_string(),
+            target.to_string(),
+            command.to_string(),
+            result.to_string(),
+        ]
+        .map(|field| field.replace(['\t', '\n'], " "))
+        .join("\t");
+
+        // One write per line, so concurrent numatop instances don't interleave entries
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        file.write_all(format!("{}\n", line).as_bytes())
+    }
+}
+
+// "root" or "root (sudo alice)", so the person behind sudo is on record
+fn acting_user() -> String {
+    // SAFETY: getuid has no preconditions and cannot fail
+    let user = user_name(unsafe { libc::getuid() });
+    match std::env::var("SUDO_USER") {
+        Ok(sudo_user) if !sudo_user.is_empty() && sudo_user != user => {
+            format!("{} (sudo {})", user, sudo_user)
+        }
+        _ => user,
+    }
+}
+
+// Local time with its UTC offset, e.g. "2024-05-01T14:03:11+02:00"
+fn timestamp() -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0) as libc::time_t;
+    // SAFETY: localtime_r only writes to the tm we pass in
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    if unsafe { libc::localtime_r(&now, &mut tm) }.is_null() {
+        return now.to_string();
+    }
+    let offset_minutes = tm.tm_gmtoff / 60;
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}{}{:02}:{:02}",
+        tm.tm_year + 1900,
+        tm.tm_mon + 1,
+        tm.tm_mday,
+        tm.tm_hour,
+        tm.tm_min,
+        tm.tm_sec,
+        if offset_minutes < 0 { '-' } else { '+' },
+        offset_minutes.abs() / 60,
+        offset_minutes.abs() % 60
+    )
+}
@@ -0,0 +1,47 @@
+use regex::{Regex, RegexBuilder};
+
+// Case-insensitive regex typed after '/', applied to every process list
+#[derive(Debug, Default)]
+pub struct ProcessFilter {
+    pub pattern: String,
+    pub regex: Option<Regex>,
+    pub error: Option<String>, // Set while the pattern doesn't compile, the last valid regex stays active
+}
+
+impl ProcessFilter {
+    pub fn is_active(&self) -> bool {
+        self.regex.is_some()
+    }
+
+    pub fn set_pattern(&mut self, pattern: String) {
+        self.pattern = pattern;
+        if self.pattern.is_empty() {
+            self.regex = None;
+            self.error = None;
+            return;
+        }
+
+        match RegexBuilder::new(&self.pattern)
+            .case_insensitive(true)
+            .build()
+        {
+            Ok(regex) => {
+                self.regex = Some(regex);
+                self.error = None;
+            }
+            Err(e) => self.error = Some(e.to_string()),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.set_pattern(String::new());
+    }
+
+    // True if any of the fields (name, PID, user, cmdline...) matches, or no filter is set
+    pub fn matches<'a>(&self, fields: impl IntoIterator<Item = &'a str>) -> bool {
+        match &self.regex {
+            Some(regex) => fields.into_iter().any(|field| regex.is_match(field)),
+            None => true,
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use crate::numa_node::NumaNode;
+use std::collections::{BTreeMap, VecDeque};
+
+// The last `capacity` samples of a metric, oldest first
+#[derive(Debug, Clone, Default)]
+pub struct History {
+    samples: VecDeque<f64>,
+    capacity: usize,
+}
+
+impl History {
+    pub fn new(capacity: usize) -> History {
+        History {
+            // Grows as samples arrive, a long window costs nothing until it has filled
+            samples: VecDeque::new(),
+            capacity,
+        }
+    }
+
+    pub fn push(&mut self, value: f64) {
+        self.samples.push_back(value);
+        while self.samples.len() > self.capacity {
+            self.samples.pop_front();
+        }
+    }
+
+    pub fn samples(&self) -> &VecDeque<f64> {
+        &self.samples
+    }
+
+    pub fn latest(&self) -> Option<f64> {
+        self.samples.back().copied()
+    }
+
+    pub fn max(&self) -> f64 {
+        self.samples.iter().copied().fold(0.0, f64::max)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct NodeHistory {
+    pub cpu_utilization: History,       // Average over the node's CPUs, %
+    pub memory_used_percent: History,   // Of the node's total memory
+    pub local_alloc_percent: History,   // Local share of the allocations landing on the node
+    pub remote_allocs_per_sec: History, // Pages allocated for tasks on other nodes
+}
+
+impl NodeHistory {
+    fn new(capacity: usize) -> NodeHistory {
+        NodeHistory {
+            cpu_utilization: History::new(capacity),
+            memory_used_percent: History::new(capacity),
+            local_alloc_percent: History::new(capacity),
+            remote_allocs_per_sec: History::new(capacity),
+        }
+    }
+}
+
+// Per-core and per-node samples over the retention window, one sample per tick
+#[derive(Debug, Clone, Default)]
+pub struct MetricsHistory {
+    pub capacity: usize, // Samples kept per metric
+    pub cores: BTreeMap<u32, History>,
+    pub nodes: BTreeMap<u32, NodeHistory>,
+}
+
+impl MetricsHistory {
+    pub fn new(capacity: usize) -> MetricsHistory {
+        MetricsHistory {
+            capacity: capacity.max(1),
+            ..Default::default()
+        }
+    }
+
+    pub fn record(&mut self, nodes: &[NumaNode]) {
+        let capacity = self.capacity;
+        for node in nodes {
+            let cpus = node.cpus.as_deref().unwrap_or_default();
+            for cpu in cpus {
+                self.cores
+                    .entry(cpu.id)
+                    .or_insert_with(|| History::new(capacity))
+                    .push(cpu.utilization);
+            }
+
+            let history = self
+                .nodes
+                .entry(node.id)
+                .or_insert_with(|| NodeHistory::new(capacity));
+            if !cpus.is_empty() {
+                let total: f64 = cpus.iter().map(|cpu| cpu.utilization).sum();
+                history.cpu_utilization.push(total / cpus.len() as f64);
+            }
+            if node.total_memory_mb > 0 {
+                history
+                    .memory_used_percent
+                    .push(node.used_memory_mb as f64 / node.total_memory_mb as f64 * 100.0);
+            }
+            // Ticks without allocations keep the last known locality
+            let local = node
+                .local_alloc_percent
+                .or(history.local_alloc_percent.latest())
+                .unwrap_or(100.0);
+            history.local_alloc_percent.push(local);
+            history
+                .remote_allocs_per_sec
+                .push(node.remote_allocs_per_sec);
+        }
+    }
+}
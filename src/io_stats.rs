@@ -0,0 +1,132 @@
+use crate::numa_node::IoDeviceKind;
+use std::{
+    error::Error,
+    fs,
+    io::{self, BufRead},
+    path::{Path, PathBuf},
+};
+
+// Sectors in /proc/diskstats are always 512 bytes, regardless of the device's block size
+const DISKSTATS_SECTOR_SIZE: u64 = 512;
+
+#[derive(Debug, Clone, Default)]
+pub struct RawIoCounters {
+    pub read_bytes: u64, // rx_bytes for NICs, sectors read times the sector size for disks
+    pub write_bytes: u64, // tx_bytes for NICs, sectors written times the sector size for disks
+}
+
+#[derive(Debug, Clone)]
+pub struct IoDeviceSample {
+    pub name: String,
+    pub kind: IoDeviceKind,
+    pub node: Option<u32>, // None if the device has no NUMA affinity (numa_node is -1)
+    pub counters: RawIoCounters,
+}
+
+// Walk up the canonical sysfs path of a device until a `numa_node` attribute is found.
+// Class devices (net/eth0, block/nvme0n1) don't carry it themselves, their PCI parent does.
+fn get_device_numa_node(sysfs_path: &Path) -> Option<u32> {
+    let canonical = fs::canonicalize(sysfs_path).ok()?;
+
+    for dir in canonical.ancestors() {
+        if dir == Path::new("/sys/devices") {
+            break;
+        }
+        if let Ok(node_str) = fs::read_to_string(dir.join("numa_node")) {
+            // -1 means the platform didn't report a node for this device
+            return node_str
+                .trim()
+                .parse::<i32>()
+                .ok()
+                .and_then(|node| if node >= 0 { Some(node as u32) } else { None });
+        }
+    }
+    None
+}
+
+fn read_counter(path: PathBuf) -> io::Result<u64> {
+    fs::read_to_string(path)?
+        .trim()
+        .parse::<u64>()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn get_net_device_samples() -> Result<Vec<IoDeviceSample>, Box<dyn Error>> {
+    let mut samples = Vec::new();
+
+    for entry in fs::read_dir("/sys/class/net")? {
+        let path = entry?.path();
+
+        // Virtual interfaces (lo, bridges, veths) have no backing device to attribute
+        if !path.join("device").exists() {
+            continue;
+        }
+
+        let name = match path.file_name() {
+            Some(name) => name.to_string_lossy().to_string(),
+            None => continue,
+        };
+        let statistics_path = path.join("statistics");
+        let (Ok(rx_bytes), Ok(tx_bytes)) = (
+            read_counter(statistics_path.join("rx_bytes")),
+            read_counter(statistics_path.join("tx_bytes")),
+        ) else {
+            continue;
+        };
+
+        samples.push(IoDeviceSample {
+            name,
+            kind: IoDeviceKind::Net,
+            node: get_device_numa_node(&path),
+            counters: RawIoCounters {
+                read_bytes: rx_bytes,
+                write_bytes: tx_bytes,
+            },
+        });
+    }
+
+    Ok(samples)
+}
+
+fn get_disk_device_samples() -> Result<Vec<IoDeviceSample>, Box<dyn Error>> {
+    let mut samples = Vec::new();
+    let file = fs::File::open("/proc/diskstats")?;
+    let reader = io::BufReader::new(file);
+
+    for line in reader.lines() {
+        let line = line?;
+        // major minor name reads merged sectors_read ms writes merged sectors_written ...
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 10 {
+            continue;
+        }
+
+        // Only whole disks backed by real hardware: partitions aren't listed under
+        // /sys/block, and loop/dm/zram devices have no `device` link
+        let block_path = Path::new("/sys/block").join(fields[2]);
+        if !block_path.join("device").exists() {
+            continue;
+        }
+
+        let sectors_read = fields[5].parse::<u64>().unwrap_or(0);
+        let sectors_written = fields[9].parse::<u64>().unwrap_or(0);
+
+        samples.push(IoDeviceSample {
+            name: fields[2].to_string(),
+            kind: IoDeviceKind::Disk,
+            node: get_device_numa_node(&block_path),
+            counters: RawIoCounters {
+                read_bytes: sectors_read * DISKSTATS_SECTOR_SIZE,
+                write_bytes: sectors_written * DISKSTATS_SECTOR_SIZE,
+            },
+        });
+    }
+
+    Ok(samples)
+}
+
+pub fn get_io_device_samples() -> Result<Vec<IoDeviceSample>, Box<dyn Error>> {
+    let mut samples = get_net_device_samples()?;
+    samples.extend(get_disk_device_samples()?);
+    Ok(samples)
+}
@@ -1,9 +1,18 @@
 pub mod app;
-use app::App;
+use app::{App, DialogStage, InputMode, View};
 use cli_log::*;
 
+mod actions;
+mod audit;
+mod filter;
+mod history;
+mod io_stats;
+mod migrations;
+mod numa_balancing;
+mod numa_maps;
 mod numa_node;
 mod proc_info;
+mod rebalance;
 mod sys_numa_info;
 mod ui;
 
@@ -17,7 +26,7 @@ use ratatui::{
     crossterm::event::{self, Event, KeyCode, MouseButton, MouseEventKind},
 };
 
-const TICK_RATE: Duration = Duration::from_millis(500); // Refresh every 0.5 second
+pub(crate) const TICK_RATE: Duration = Duration::from_millis(500); // Refresh every 0.5 second
 
 fn draw(app: &mut App, frame: &mut Frame) {
     ui::draw(app, frame);
@@ -30,20 +39,86 @@ fn handle_events(app: &mut App, last_tick: Instant) -> io::Result<()> {
 
     if event::poll(timeout)? {
         match event::read()? {
-            Event::Key(key) => {
-                if key.code == KeyCode::Char('q') {
-                    debug!("q pressed");
-                    app.exit();
-                } else if key.code == KeyCode::Esc {
-                    app.hide_popup();
+            Event::Key(key) if app.action_dialog.is_some() => {
+                let stage = app.action_dialog.as_ref().map(|d| d.stage);
+                match (stage, key.code) {
+                    (Some(DialogStage::Input), KeyCode::Char(c)) => app.dialog_push_char(c),
+                    (Some(DialogStage::Input), KeyCode::Backspace) => app.dialog_pop_char(),
+                    (Some(DialogStage::Input), KeyCode::Enter) => app.dialog_submit(),
+                    (Some(DialogStage::Input), KeyCode::Esc) => app.close_dialog(),
+                    (Some(DialogStage::Confirm), KeyCode::Char('y')) => app.dialog_confirm(true),
+                    (Some(DialogStage::Confirm), _) => app.dialog_confirm(false),
+                    _ => {}
                 }
             }
-            Event::Mouse(mouse) => {
-                if mouse.kind == MouseEventKind::Down(MouseButton::Left) {
-                    // Store mouse click coordinates for UI processing
-                    app.handle_mouse_click(mouse.column, mouse.row);
+            Event::Key(key) if app.input_mode == InputMode::Search => match key.code {
+                KeyCode::Char(c) => app.push_search_char(c),
+                KeyCode::Backspace => app.pop_search_char(),
+                KeyCode::Enter => app.finish_search(true),
+                KeyCode::Esc => app.finish_search(false),
+                _ => {}
+            },
+            Event::Key(key) => {
+                let popup_open = app.view == View::Nodes && app.popup_state.show;
+                // Left/Right move the node cursor of the node view and of the chart
+                let node_cursor =
+                    (app.view == View::Nodes && !popup_open) || app.view == View::Chart;
+                match key.code {
+                    KeyCode::Char('q') => {
+                        debug!("q pressed");
+                        app.exit();
+                    }
+                    KeyCode::Char('p') => app.toggle_process_view(),
+                    KeyCode::Char('m') => app.toggle_migration_view(),
+                    KeyCode::Char('b') => app.toggle_suggestion_view(),
+                    KeyCode::Char('c') => app.toggle_chart_view(),
+                    KeyCode::Char('/') => app.start_search(),
+                    KeyCode::Char('k') => app.toggle_kernel_threads(),
+                    KeyCode::Char('a') => app.open_affinity_dialog(),
+                    KeyCode::Char('M') => app.open_migrate_dialog(),
+                    KeyCode::Char('P') => app.open_policy_dialog(),
+                    KeyCode::Char('r') => app.open_run_dialog(),
+                    KeyCode::Char('x') => app.open_signal_dialog(),
+                    KeyCode::Char('n') => app.open_priority_dialog(),
+                    KeyCode::Char('d') => app.toggle_dry_run(),
+                    KeyCode::Char('g') if popup_open => app.toggle_popup_grouping(),
+                    KeyCode::Char(c @ '1'..='8') if popup_open => {
+                        app.toggle_popup_column(c as usize - '1' as usize)
+                    }
+                    KeyCode::Char('s') if popup_open => app.cycle_popup_sort_key(),
+                    KeyCode::Char('S') if popup_open => app.reverse_popup_sort(),
+                    KeyCode::Char(c @ '1'..='3') if app.view == View::Chart => {
+                        app.toggle_chart_metric(c as usize - '1' as usize)
+                    }
+                    KeyCode::Char(' ') if app.view == View::Chart => app.toggle_chart_node(),
+                    KeyCode::Char('+') if app.view == View::Chart => app.zoom_chart(true),
+                    KeyCode::Char('-') if app.view == View::Chart => app.zoom_chart(false),
+                    KeyCode::Left if node_cursor => app.select_adjacent_node(false),
+                    KeyCode::Right if node_cursor => app.select_adjacent_node(true),
+                    KeyCode::Up => app.select_previous(),
+                    KeyCode::Down => app.select_next(),
+                    KeyCode::Enter if app.view == View::Processes => app.show_process_detail(),
+                    KeyCode::Enter if popup_open => app.show_popup_process_detail(),
+                    KeyCode::Enter if app.view == View::Suggestions => {
+                        app.apply_selected_suggestion()
+                    }
+                    KeyCode::Esc => match app.view {
+                        View::Nodes => app.hide_popup(),
+                        View::Processes => app.toggle_process_view(),
+                        View::Migrations => app.toggle_migration_view(),
+                        View::Suggestions => app.toggle_suggestion_view(),
+                        View::Chart => app.toggle_chart_view(),
+                        View::ProcessDetail => app.hide_process_detail(),
+                    },
+                    _ => {}
                 }
             }
+            Event::Mouse(mouse) if mouse.kind == MouseEventKind::Down(MouseButton::Left) => {
+                // Store mouse click coordinates for UI processing
+                app.handle_mouse_click(mouse.column, mouse.row);
+            }
+            Event::Mouse(mouse) if mouse.kind == MouseEventKind::ScrollDown => app.select_next(),
+            Event::Mouse(mouse) if mouse.kind == MouseEventKind::ScrollUp => app.select_previous(),
             _ => {}
         }
     }
@@ -6,14 +6,88 @@ use ratatui::crossterm::{
 };
 
 use std::io;
+use std::path::PathBuf;
+use std::process;
+use std::time::Duration;
+
+// Two samples a second, so an hour is about 56 KiB for each core and node metric
+const MAX_HISTORY_SECS: u64 = 60 * 60;
+
+const USAGE: &str = "\
+Usage: numatop [OPTIONS]
+
+Options:
+  --read-only         Disable every action that changes system state
+  --dry-run           Start with dry run on: confirmed actions are logged, not applied
+  --audit-log PATH    Append confirmed actions to PATH
+                      (default: $XDG_STATE_HOME/numatop/audit.log)
+  --no-audit-log      Don't keep an audit log
+  --history SECONDS   How far back the node sparklines go (default: 300, at most 3600)
+  -h, --help          Print this help";
+
+// Command line options, applied to the app once it is created
+#[derive(Default)]
+struct Args {
+    read_only: bool,
+    dry_run: bool,
+    audit_log: Option<Option<PathBuf>>, // None keeps the default, Some(None) disables it
+    history: Option<Duration>,
+}
 
 fn main() -> io::Result<()> {
     init_cli_log!();
+    let args = parse_args();
+    let mut app = App::new();
+    app.set_read_only(args.read_only);
+    app.dry_run = args.dry_run;
+    if let Some(path) = args.audit_log {
+        app.set_audit_log_path(path);
+    }
+    if let Some(retention) = args.history {
+        app.set_history_retention(retention);
+    }
+
     io::stdout().execute(EnableMouseCapture)?;
     let mut terminal = ratatui::init();
-    let mut app = App::new();
     let res = run_app(&mut terminal, &mut app);
     ratatui::restore();
     io::stdout().execute(DisableMouseCapture)?;
     res
 }
+
+// Exits on --help and on bad arguments, before the terminal is taken over
+fn parse_args() -> Args {
+    let mut parsed = Args::default();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--read-only" => parsed.read_only = true,
+            "--dry-run" => parsed.dry_run = true,
+            "--audit-log" => match args.next() {
+                Some(path) => parsed.audit_log = Some(Some(PathBuf::from(path))),
+                None => usage_error("--audit-log needs a path"),
+            },
+            "--no-audit-log" => parsed.audit_log = Some(None),
+            "--history" => match args.next().and_then(|secs| secs.parse::<u64>().ok()) {
+                Some(secs) if secs > 0 && secs <= MAX_HISTORY_SECS => {
+                    parsed.history = Some(Duration::from_secs(secs))
+                }
+                _ => usage_error(&format!(
+                    "--history needs a number of seconds from 1 to {}",
+                    MAX_HISTORY_SECS
+                )),
+            },
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            _ => usage_error(&format!("unknown option {}", arg)),
+        }
+    }
+    parsed
+}
+
+fn usage_error(message: &str) -> ! {
+    eprintln!("numatop: {}\n\n{}", message, USAGE);
+    process::exit(2);
+}
@@ -0,0 +1,154 @@
+use crate::proc_info::{TaskStat, get_process_owner_and_cmdline};
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+// Samples of each thread's node kept, enough to fill the detail view's timeline
+pub const RECENT_NODE_SAMPLES: usize = 60;
+// Samples further apart than this didn't follow each other tick by tick
+const MAX_SAMPLE_GAP: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone)]
+pub struct ThreadMigrations {
+    pub tgid: u32,
+    pub tid: u32,
+    pub thread_name: String,
+    pub process_name: String,
+    pub user: String,    // Read once, when the process is first seen
+    pub cmdline: String, // Likewise, empty for kernel threads
+    pub is_kernel_thread: bool,
+    pub cpu: u32,                            // CPU seen at the latest sample
+    pub core_migrations: u64,                // Samples where the CPU changed within the same node
+    pub node_migrations: u64,                // Samples where the CPU changed to another node
+    pub recent_nodes: VecDeque<Option<u32>>, // Node at each of the latest samples, oldest first
+}
+
+impl ThreadMigrations {
+    pub fn total_migrations(&self) -> u64 {
+        self.core_migrations + self.node_migrations
+    }
+
+    // The fields the '/' filter matches, the same as ProcessInfo::filter_fields
+    pub fn filter_fields(&self) -> [String; 6] {
+        [
+            self.tgid.to_string(),
+            self.tid.to_string(),
+            self.thread_name.clone(),
+            self.process_name.clone(),
+            self.user.clone(),
+            self.cmdline.clone(),
+        ]
+    }
+}
+
+// Compares each thread's last-run CPU (stat field 39) between ticks. Migrations that happen
+// and revert between two samples are invisible, so counts are a lower bound.
+#[derive(Debug, Default)]
+pub struct MigrationTracker {
+    pub threads: HashMap<u32, ThreadMigrations>, // Keyed by TID
+    pub samples: u64,
+    pub sampled_at: Option<Instant>,
+}
+
+impl MigrationTracker {
+    pub fn record(&mut self, tasks: &[(u32, u32, TaskStat)], cpu_nodes: &HashMap<u32, u32>) {
+        // After a pause in sampling the recent nodes would no longer be one tick apart
+        let consecutive = self
+            .sampled_at
+            .is_some_and(|sampled| sampled.elapsed() <= MAX_SAMPLE_GAP);
+
+        // Main threads carry the process name
+        let process_names: HashMap<u32, &str> = tasks
+            .iter()
+            .filter(|(tgid, tid, _)| tgid == tid)
+            .map(|(tgid, _, stat)| (*tgid, stat.comm.as_str()))
+            .collect();
+
+        // Owner and cmdline of the processes already tracked, new ones are read below
+        let mut owners: HashMap<u32, (String, String)> = HashMap::new();
+        for thread in self.threads.values() {
+            owners
+                .entry(thread.tgid)
+                .or_insert_with(|| (thread.user.clone(), thread.cmdline.clone()));
+        }
+
+        let mut threads = HashMap::with_capacity(tasks.len());
+        for (tgid, tid, stat) in tasks {
+            let mut thread = match self.threads.remove(tid) {
+                // A recycled TID belongs to a different thread, start over
+                Some(prev) if prev.tgid == *tgid => prev,
+                _ => {
+                    let (user, cmdline) = owners
+                        .entry(*tgid)
+                        .or_insert_with(|| get_process_owner_and_cmdline(*tgid))
+                        .clone();
+                    ThreadMigrations {
+                        tgid: *tgid,
+                        tid: *tid,
+                        thread_name: String::new(),
+                        process_name: String::new(),
+                        user,
+                        cmdline,
+                        is_kernel_thread: stat.is_kernel_thread(*tgid),
+                        cpu: stat.cpu,
+                        core_migrations: 0,
+                        node_migrations: 0,
+                        recent_nodes: VecDeque::new(),
+                    }
+                }
+            };
+
+            if thread.cpu != stat.cpu {
+                if cpu_nodes.get(&thread.cpu) == cpu_nodes.get(&stat.cpu) {
+                    thread.core_migrations += 1;
+                } else {
+                    thread.node_migrations += 1;
+                }
+            }
+
+            thread.cpu = stat.cpu;
+            if !consecutive {
+                thread.recent_nodes.clear();
+            }
+            thread
+                .recent_nodes
+                .push_back(cpu_nodes.get(&stat.cpu).copied());
+            while thread.recent_nodes.len() > RECENT_NODE_SAMPLES {
+                thread.recent_nodes.pop_front();
+            }
+            thread.thread_name = stat.comm.clone();
+            if let Some(process_name) = process_names.get(tgid) {
+                thread.process_name = process_name.to_string();
+            }
+
+            threads.insert(*tid, thread);
+        }
+
+        // Threads missing from this scan have exited
+        self.threads = threads;
+        self.samples += 1;
+        self.sampled_at = Some(Instant::now());
+    }
+
+    // Sampled during the current tick, so the CPU of each thread is up to date
+    pub fn is_current(&self) -> bool {
+        self.sampled_at
+            .is_some_and(|sampled| sampled.elapsed() < crate::TICK_RATE)
+    }
+
+    // Threads that moved at least once, cross-node migrations first
+    pub fn most_migrating(&self) -> Vec<&ThreadMigrations> {
+        let mut threads: Vec<&ThreadMigrations> = self
+            .threads
+            .values()
+            .filter(|t| t.total_migrations() > 0)
+            .collect();
+        threads.sort_by_key(|t| {
+            (
+                std::cmp::Reverse(t.node_migrations),
+                std::cmp::Reverse(t.core_migrations),
+                t.tid,
+            )
+        });
+        threads
+    }
+}
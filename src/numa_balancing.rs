@@ -0,0 +1,98 @@
+use std::collections::BTreeMap;
+use std::{fs, io};
+
+#[derive(Debug, Clone, Default)]
+pub struct NodeFaults {
+    pub task_private: u64,
+    pub task_shared: u64,
+    pub group_private: u64,
+    pub group_shared: u64,
+}
+
+// Automatic NUMA balancing state of a task, from the NUMA section of /proc/PID/sched
+#[derive(Debug, Clone, Default)]
+pub struct NumaSchedStats {
+    pub pages_migrated: u64,
+    pub preferred_node: Option<u32>, // None while the kernel has no preference (-1)
+    pub total_faults: u64,
+    pub current_node: Option<u32>,
+    pub numa_group_id: u32,
+    pub node_faults: BTreeMap<u32, NodeFaults>,
+}
+
+// "numa_preferred_nid                           :                   -1"
+fn parse_sched_value(line: &str, key: &str) -> Option<i64> {
+    let (name, value) = line.split_once(':')?;
+    if name.trim() != key {
+        return None;
+    }
+    value.trim().parse().ok()
+}
+
+// "key=value, key=value" or "key=value key=value" into a map
+fn parse_key_values(line: &str) -> BTreeMap<&str, &str> {
+    line.split(|c: char| c == ',' || c.is_whitespace())
+        .filter_map(|token| token.split_once('='))
+        .collect()
+}
+
+// Returns None when the kernel wasn't built with CONFIG_NUMA_BALANCING
+pub fn parse_numa_sched_stats(pid: u32) -> io::Result<Option<NumaSchedStats>> {
+    let content = fs::read_to_string(format!("/proc/{}/sched", pid))?;
+    let mut stats = NumaSchedStats::default();
+    let mut found = false;
+
+    for line in content.lines() {
+        if let Some(value) = parse_sched_value(line, "numa_pages_migrated") {
+            stats.pages_migrated = value.max(0) as u64;
+            found = true;
+        } else if let Some(value) = parse_sched_value(line, "numa_preferred_nid") {
+            stats.preferred_node = u32::try_from(value).ok();
+        } else if let Some(value) = parse_sched_value(line, "total_numa_faults") {
+            stats.total_faults = value.max(0) as u64;
+        } else if line.starts_with("current_node=") {
+            let values = parse_key_values(line);
+            stats.current_node = values.get("current_node").and_then(|v| v.parse().ok());
+            stats.numa_group_id = values
+                .get("numa_group_id")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0);
+        } else if let Some(rest) = line.strip_prefix("numa_faults ") {
+            let values = parse_key_values(rest);
+            let get = |key: &str| values.get(key).and_then(|v| v.parse().ok()).unwrap_or(0);
+            if let Some(node_id) = values.get("node").and_then(|v| v.parse().ok()) {
+                stats.node_faults.insert(
+                    node_id,
+                    NodeFaults {
+                        task_private: get("task_private"),
+                        task_shared: get("task_shared"),
+                        group_private: get("group_private"),
+                        group_shared: get("group_shared"),
+                    },
+                );
+            }
+        }
+    }
+
+    Ok(if found { Some(stats) } else { None })
+}
+
+// /proc/sys/kernel/numa_balancing: 0 off, 1 normal, 2 memory tiering, 3 both.
+// None when the sysctl doesn't exist.
+pub fn get_numa_balancing_mode() -> Option<u32> {
+    fs::read_to_string("/proc/sys/kernel/numa_balancing")
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+pub fn describe_numa_balancing_mode(mode: Option<u32>) -> &'static str {
+    match mode {
+        None => "unavailable",
+        Some(0) => "disabled",
+        Some(1) => "enabled",
+        Some(2) => "memory tiering",
+        Some(_) => "enabled + memory tiering",
+    }
+}
@@ -0,0 +1,96 @@
+use std::collections::BTreeMap;
+use std::{fs, io};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum MappingBacking {
+    File(String),
+    Heap,
+    Stack,
+    #[default]
+    Anon,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct NumaMapping {
+    pub address: u64,
+    pub policy: String, // default, bind:0-1, interleave:0-1, prefer:1, local...
+    pub backing: MappingBacking,
+    pub pages_per_node: BTreeMap<u32, u64>, // N0=..., N1=... counts
+    pub page_size_kb: u64,
+}
+
+impl NumaMapping {
+    pub fn node_memory_kb(&self, node_id: u32) -> u64 {
+        self.pages_per_node.get(&node_id).copied().unwrap_or(0) * self.page_size_kb
+    }
+
+    pub fn total_pages(&self) -> u64 {
+        self.pages_per_node.values().sum()
+    }
+}
+
+// Parse a single /proc/PID/numa_maps line, e.g.
+// "7f1c2a000000 default file=/usr/lib/libc.so.6 mapped=42 N0=30 N1=12 kernelpagesize_kB=4"
+fn parse_numa_maps_line(line: &str) -> Option<NumaMapping> {
+    let mut parts = line.split_whitespace();
+    let address = u64::from_str_radix(parts.next()?, 16).ok()?;
+    let policy = parts.next()?.to_string();
+
+    let mut mapping = NumaMapping {
+        address,
+        policy,
+        page_size_kb: 4,
+        ..Default::default()
+    };
+
+    for token in parts {
+        match token {
+            "heap" => mapping.backing = MappingBacking::Heap,
+            "stack" => mapping.backing = MappingBacking::Stack,
+            _ => {}
+        }
+
+        if let Some((key, value)) = token.split_once('=') {
+            if key == "file" {
+                mapping.backing = MappingBacking::File(value.to_string());
+            } else if key == "kernelpagesize_kB" {
+                mapping.page_size_kb = value.parse().unwrap_or(4);
+            } else if let Some(node_str) = key.strip_prefix('N')
+                && let (Ok(node_id), Ok(pages)) = (node_str.parse::<u32>(), value.parse::<u64>())
+            {
+                mapping.pages_per_node.insert(node_id, pages);
+            }
+        }
+    }
+
+    Some(mapping)
+}
+
+pub fn parse_numa_maps(pid: u32) -> io::Result<Vec<NumaMapping>> {
+    let content = fs::read_to_string(format!("/proc/{}/numa_maps", pid))?;
+    Ok(content.lines().filter_map(parse_numa_maps_line).collect())
+}
+
+// Sum the resident memory of all mappings per node, in KB
+pub fn get_node_memory_kb(mappings: &[NumaMapping]) -> BTreeMap<u32, u64> {
+    let mut node_memory_kb = BTreeMap::new();
+    for mapping in mappings {
+        for node_id in mapping.pages_per_node.keys() {
+            *node_memory_kb.entry(*node_id).or_insert(0) += mapping.node_memory_kb(*node_id);
+        }
+    }
+    node_memory_kb
+}
+
+// Policy of most mappings. Mappings without a policy of their own report the task's
+// policy, so this is what set_mempolicy or numactl applied to the process.
+pub fn dominant_policy(mappings: &[NumaMapping]) -> Option<&str> {
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for mapping in mappings {
+        *counts.entry(mapping.policy.as_str()).or_insert(0) += 1;
+    }
+    counts
+        .into_iter()
+        .max_by_key(|(_, count)| *count)
+        .map(|(policy, _)| policy)
+}
@@ -4,10 +4,35 @@ pub struct CpuCore {
     pub utilization: f64,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IoDeviceKind {
+    Net,
+    Disk,
+}
+
+#[derive(Debug, Clone)]
+pub struct IoDevice {
+    pub name: String,
+    pub kind: IoDeviceKind,
+    pub read_bytes_per_sec: f64,  // rx for NICs
+    pub write_bytes_per_sec: f64, // tx for NICs
+}
+
+// Allocation counters of /sys/devices/system/node/nodeN/numastat, in pages
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NodeNumaStat {
+    pub local_node: u64, // Allocated here for a task running on this node
+    pub other_node: u64, // Allocated here for a task running on another node
+}
+
 #[derive(Debug, Clone)]
 pub struct NumaNode {
     pub id: u32,
     pub cpus: Option<Vec<CpuCore>>, // None if CPULess, Some(vec![]) if has CPU region but no listed CPUs (unlikely for actual CPUs)
     pub total_memory_mb: u64,
     pub used_memory_mb: u64,
+    pub io_devices: Vec<IoDevice>, // NICs and disks attached to this node
+    pub numastat: Option<NodeNumaStat>,
+    pub local_alloc_percent: Option<f64>, // Local share of the allocations since the last tick
+    pub remote_allocs_per_sec: f64,       // Pages allocated here for tasks on other nodes
 }
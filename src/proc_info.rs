@@ -1,6 +1,10 @@
-use std::collections::HashMap;
+use crate::numa_balancing::{NumaSchedStats, parse_numa_sched_stats};
+use crate::numa_maps::{get_node_memory_kb, parse_numa_maps};
+use crate::sys_numa_info::parse_cpulist;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::error::Error;
 use std::io::{BufRead, ErrorKind};
+use std::sync::OnceLock;
 use std::{fs, io};
 
 #[derive(Debug, Clone, Default)]
@@ -46,25 +50,25 @@ pub fn parse_proc_stat_for_cores(
         if line.starts_with("cpu") && !line.starts_with("cpu ") {
             let mut parts = line.split_whitespace();
             let cpu_label = parts.next().ok_or("Missing CPU label")?;
-            if let Ok(core_id) = cpu_label[3..].parse::<u32>() {
-                if cores_to_fetch.contains(&core_id) {
-                    let times: Vec<u64> = parts.map(|s| s.parse().unwrap_or(0)).collect();
-                    if times.len() >= 8 {
-                        // user, nice, system, idle, iowait, irq, softirq, steal
-                        all_core_times.insert(
-                            core_id,
-                            RawCpuTimes {
-                                user: times[0],
-                                nice: times[1],
-                                system: times[2],
-                                idle: times[3],
-                                iowait: times[4],
-                                irq: times[5],
-                                softirq: times[6],
-                                steal: times[7],
-                            },
-                        );
-                    }
+            if let Ok(core_id) = cpu_label[3..].parse::<u32>()
+                && cores_to_fetch.contains(&core_id)
+            {
+                let times: Vec<u64> = parts.map(|s| s.parse().unwrap_or(0)).collect();
+                if times.len() >= 8 {
+                    // user, nice, system, idle, iowait, irq, softirq, steal
+                    all_core_times.insert(
+                        core_id,
+                        RawCpuTimes {
+                            user: times[0],
+                            nice: times[1],
+                            system: times[2],
+                            idle: times[3],
+                            iowait: times[4],
+                            irq: times[5],
+                            softirq: times[6],
+                            steal: times[7],
+                        },
+                    );
                 }
             }
         }
@@ -72,93 +76,416 @@ pub fn parse_proc_stat_for_cores(
     Ok(all_core_times)
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ProcessInfo {
-    pub pid: u32,
-    pub name: String,
+    pub tgid: u32,              // Process ID (thread group leader)
+    pub tid: u32,               // Thread ID, equal to tgid for the main thread
+    pub thread_name: String,    // comm of the thread
+    pub process_name: String,   // comm of the thread group leader
+    pub cpu: u32,               // CPU the task last ran on
+    pub cpu_time_ticks: u64,    // utime + stime at the last sample
+    pub cpu_percent: f64,       // CPU usage between the last two samples
+    pub user: String,           // Owner of the task, from the real UID
+    pub cmdline: String,        // Process command line, empty for kernel threads
+    pub is_kernel_thread: bool, // PF_KTHREAD or spawned by kthreadd, has no cmdline
+    pub state: char,            // R running, S sleeping, D uninterruptible, ...
+    pub priority: i64,          // Kernel priority as shown by ps (-100..39)
+    pub nice: i64,              // -20..19, 0 for real-time tasks
+    pub policy: u32,            // SCHED_* scheduling policy
+    pub rss_kb: u64,            // Resident set of the whole process
+    pub start_time: u64,        // Seconds since the epoch
+    pub cpus_allowed: Vec<u32>, // Cpus_allowed_list from /proc/PID/status
+    pub mems_allowed: Vec<u32>, // Mems_allowed_list from /proc/PID/status
 }
 
-fn get_process_info(pid: u32) -> io::Result<ProcessInfo> {
-    // Read thread/process name from /proc/PID/comm
-    let comm_path = format!("/proc/{}/comm", pid);
-    let name = fs::read_to_string(&comm_path)?.trim().to_string();
+impl ProcessInfo {
+    // Allowed CPUs belong to more than one NUMA node, so the scheduler may move it across nodes
+    pub fn cpus_span_nodes(&self, cpu_nodes: &HashMap<u32, u32>) -> bool {
+        let nodes: HashSet<u32> = self
+            .cpus_allowed
+            .iter()
+            .filter_map(|cpu| cpu_nodes.get(cpu))
+            .copied()
+            .collect();
+        nodes.len() > 1
+    }
+
+    // Fields the '/' filter is matched against
+    pub fn filter_fields(&self) -> [String; 6] {
+        [
+            self.tgid.to_string(),
+            self.tid.to_string(),
+            self.thread_name.clone(),
+            self.process_name.clone(),
+            self.user.clone(),
+            self.cmdline.clone(),
+        ]
+    }
 
-    Ok(ProcessInfo { pid, name })
+    // The node the task runs on isn't one it may allocate memory from, so all its memory is remote
+    pub fn runs_outside_mems_allowed(&self, cpu_nodes: &HashMap<u32, u32>) -> bool {
+        match cpu_nodes.get(&self.cpu) {
+            Some(node_id) => !self.mems_allowed.contains(node_id),
+            None => false,
+        }
+    }
 }
 
-fn get_current_cpu_core(pid: u32) -> io::Result<u32> {
-    // Construct the path to the thread's stat file
-    let stat_path = format!("/proc/{}/stat", pid);
+fn task_path(tgid: u32, tid: u32) -> String {
+    format!("/proc/{}/task/{}", tgid, tid)
+}
 
-    // Read the content of the stat file
-    let stat_content = fs::read_to_string(&stat_path)?;
+#[derive(Debug, Clone, Default)]
+struct TaskStatus {
+    uid: u32, // Real UID
+    cpus_allowed: Vec<u32>,
+    mems_allowed: Vec<u32>,
+}
+
+fn parse_task_status(tgid: u32, tid: u32) -> io::Result<TaskStatus> {
+    let status = fs::read_to_string(format!("{}/status", task_path(tgid, tid)))?;
+    let mut task_status = TaskStatus::default();
+
+    for line in status.lines() {
+        if let Some(value) = line.strip_prefix("Uid:") {
+            // Real, effective, saved set, filesystem
+            task_status.uid = value
+                .split_whitespace()
+                .next()
+                .and_then(|uid| uid.parse().ok())
+                .unwrap_or(0);
+        } else if let Some(value) = line.strip_prefix("Cpus_allowed_list:") {
+            task_status.cpus_allowed = parse_cpulist(value);
+        } else if let Some(value) = line.strip_prefix("Mems_allowed_list:") {
+            task_status.mems_allowed = parse_cpulist(value);
+        }
+    }
+
+    Ok(task_status)
+}
+
+// UID -> user name from /etc/passwd, read once
+pub fn user_name(uid: u32) -> String {
+    static USERS: OnceLock<HashMap<u32, String>> = OnceLock::new();
+    let users = USERS.get_or_init(|| {
+        fs::read_to_string("/etc/passwd")
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|line| {
+                // name:password:UID:GID:GECOS:home:shell
+                let mut fields = line.split(':');
+                let name = fields.next()?;
+                let uid = fields.nth(1)?.parse().ok()?;
+                Some((uid, name.to_string()))
+            })
+            .collect()
+    });
+    users.get(&uid).cloned().unwrap_or_else(|| uid.to_string())
+}
+
+// Arguments are NUL-separated; kernel threads have an empty cmdline
+fn read_cmdline(tgid: u32) -> String {
+    fs::read(format!("/proc/{}/cmdline", tgid))
+        .map(|bytes| {
+            String::from_utf8_lossy(&bytes)
+                .split('\0')
+                .filter(|arg| !arg.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .unwrap_or_default()
+}
 
-    // Split the content by spaces to get individual fields
-    let fields: Vec<&str> = stat_content.split_whitespace().collect();
-
-    // The 39th field (index 38) contains the current CPU core ID
-    if let Some(cpu_field) = fields.get(38) {
-        // Try to parse the field into a u32
-        match cpu_field.parse::<u32>() {
-            Ok(cpu_core) => Ok(cpu_core),
-            Err(_) => Err(io::Error::new(
-                ErrorKind::InvalidData,
-                "Failed to parse CPU core ID",
-            )),
+// Control group paths from /proc/PID/cgroup, e.g. "0::/system.slice/nginx.service".
+// cgroup v2 has a single line, v1 one per hierarchy; identical paths are only shown once.
+pub fn read_cgroup(pid: u32) -> io::Result<String> {
+    let content = fs::read_to_string(format!("/proc/{}/cgroup", pid))?;
+    let mut paths: Vec<&str> = Vec::new();
+    for line in content.lines() {
+        // hierarchy-ID:controller-list:path
+        if let Some(path) = line.splitn(3, ':').nth(2)
+            && !paths.contains(&path)
+        {
+            paths.push(path);
         }
+    }
+    Ok(paths.join(", "))
+}
+
+// Owner and command line of a process, for lists built from task stats alone
+pub fn get_process_owner_and_cmdline(tgid: u32) -> (String, String) {
+    let user = parse_task_status(tgid, tgid)
+        .map(|status| user_name(status.uid))
+        .unwrap_or_default();
+    (user, read_cmdline(tgid))
+}
+
+pub fn get_process_info(tgid: u32, tid: u32) -> io::Result<ProcessInfo> {
+    // Read thread name from /proc/PID/task/TID/comm and process name from /proc/PID/comm
+    let thread_name = fs::read_to_string(format!("{}/comm", task_path(tgid, tid)))?
+        .trim()
+        .to_string();
+    let process_name = if tgid == tid {
+        thread_name.clone()
     } else {
-        // If the 39th field doesn't exist, return an error
-        Err(io::Error::new(
-            ErrorKind::NotFound,
-            "Could not find CPU core ID in /proc/PID/stat",
-        ))
+        fs::read_to_string(format!("/proc/{}/comm", tgid))?
+            .trim()
+            .to_string()
+    };
+    let stat = parse_task_stat(tgid, tid)?;
+    let status = parse_task_status(tgid, tid)?;
+
+    Ok(ProcessInfo {
+        tgid,
+        tid,
+        thread_name,
+        process_name,
+        cpu: stat.cpu,
+        cpu_time_ticks: stat.cpu_time_ticks,
+        cpu_percent: 0.0,
+        user: user_name(status.uid),
+        cmdline: read_cmdline(tgid),
+        is_kernel_thread: stat.is_kernel_thread(tgid),
+        state: stat.state,
+        priority: stat.priority,
+        nice: stat.nice,
+        policy: stat.policy,
+        rss_kb: stat.rss_pages * page_size_kb(),
+        start_time: boot_time() + (stat.start_time_ticks as f64 / clock_ticks_per_sec()) as u64,
+        cpus_allowed: status.cpus_allowed,
+        mems_allowed: status.mems_allowed,
+    })
+}
+
+#[derive(Debug, Clone)]
+pub struct ProcessNumaMemory {
+    pub info: ProcessInfo,
+    pub node_memory_kb: BTreeMap<u32, u64>, // Resident memory per node, from numa_maps
+    pub numa_sched: Option<NumaSchedStats>, // None without CONFIG_NUMA_BALANCING
+}
+
+impl ProcessNumaMemory {
+    pub fn total_kb(&self) -> u64 {
+        self.node_memory_kb.values().sum()
+    }
+
+    // The node holding most of the process's resident memory
+    pub fn home_node(&self) -> Option<u32> {
+        self.node_memory_kb
+            .iter()
+            .max_by_key(|(_, kb)| **kb)
+            .map(|(node_id, _)| *node_id)
+    }
+
+    pub fn home_node_percent(&self) -> f64 {
+        let total_kb = self.total_kb();
+        match self.home_node() {
+            Some(node_id) if total_kb > 0 => {
+                self.node_memory_kb[&node_id] as f64 / total_kb as f64 * 100.0
+            }
+            _ => 0.0,
+        }
     }
 }
 
-pub fn get_processes_currently_on_core(cpu_core_id: u32) -> io::Result<Vec<ProcessInfo>> {
+pub fn get_process_numa_memory() -> io::Result<Vec<ProcessNumaMemory>> {
     let mut processes = Vec::new();
 
-    // Read /proc directory to get all process directories
-    let proc_entries = fs::read_dir("/proc")?;
-
-    for entry in proc_entries {
-        let entry = entry?;
-        let path = entry.path();
-
-        // Check if directory name is a PID (numeric)
-        if let Some(dir_name) = path.file_name().and_then(|n| n.to_str()) {
-            if let Ok(pid) = dir_name.parse::<u32>() {
-                // For each process, iterate through its threads in /proc/PID/task/
-                let task_path = format!("/proc/{}/task", pid);
-
-                if let Ok(task_entries) = fs::read_dir(task_path) {
-                    for task_entry in task_entries.filter_map(Result::ok) {
-                        // The directory name for each task is its Thread ID (TID)
-                        if let Some(tid_str) = task_entry.file_name().to_str() {
-                            if let Ok(tid) = tid_str.parse::<u32>() {
-                                // Check if this specific thread is on the target core
-                                if let Ok(current_cpu) = get_current_cpu_core(tid) {
-                                    if current_cpu == cpu_core_id {
-                                        // Get the thread's info and add it to our list.
-                                        // The 'pid' field in ProcessInfo will hold the TID.
-                                        if let Ok(process_info) = get_process_info(tid) {
-                                            processes.push(process_info);
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
+    for entry in fs::read_dir("/proc")?.filter_map(Result::ok) {
+        if let Some(dir_name) = entry.file_name().to_str()
+            && let Ok(pid) = dir_name.parse::<u32>()
+        {
+            // Processes may exit or deny access (other users' numa_maps) while we walk /proc
+            let Ok(mappings) = parse_numa_maps(pid) else {
+                continue;
+            };
+            let node_memory_kb = get_node_memory_kb(&mappings);
+
+            // Kernel threads have no user mappings, nothing to show
+            if node_memory_kb.values().all(|kb| *kb == 0) {
+                continue;
+            }
+
+            if let Ok(info) = get_process_info(pid, pid) {
+                processes.push(ProcessNumaMemory {
+                    info,
+                    node_memory_kb,
+                    numa_sched: parse_numa_sched_stats(pid).ok().flatten(),
+                });
             }
         }
     }
 
-    // Sort by TID in reverse order
-    processes.sort_by(|a, b| b.pid.cmp(&a.pid));
-
-    // Remove any potential duplicates
-    processes.dedup();
+    // Largest resident set first
+    processes.sort_by_key(|p| std::cmp::Reverse(p.total_kb()));
 
     Ok(processes)
 }
+
+#[derive(Debug, Clone, Default)]
+pub struct TaskStat {
+    pub comm: String,          // Field 2, without the parentheses
+    pub state: char,           // Field 3
+    pub ppid: u32,             // Field 4
+    pub flags: u64,            // Field 9: PF_* flags
+    pub cpu_time_ticks: u64,   // Fields 14 + 15: utime + stime, in clock ticks
+    pub priority: i64,         // Field 18
+    pub nice: i64,             // Field 19
+    pub start_time_ticks: u64, // Field 22: clock ticks after boot
+    pub rss_pages: u64,        // Field 24
+    pub cpu: u32,              // Field 39: CPU the task last ran on
+    pub policy: u32,           // Field 41
+}
+
+// include/linux/sched.h
+const PF_KTHREAD: u64 = 0x00200000;
+const KTHREADD_PID: u32 = 2;
+
+impl TaskStat {
+    // kthreadd and everything it spawns (ksoftirqd, migration/N, kworkers...)
+    pub fn is_kernel_thread(&self, tgid: u32) -> bool {
+        self.flags & PF_KTHREAD != 0 || self.ppid == KTHREADD_PID || tgid == KTHREADD_PID
+    }
+}
+
+// Parse the fields we need from /proc/PID/task/TID/stat
+pub fn parse_task_stat(tgid: u32, tid: u32) -> io::Result<TaskStat> {
+    // Construct the path to the thread's stat file
+    let stat_path = format!("{}/stat", task_path(tgid, tid));
+
+    // Read the content of the stat file
+    let stat_content = fs::read_to_string(&stat_path)?;
+
+    // The comm field (2) is wrapped in parentheses and may itself contain spaces,
+    // so only split what comes after its closing parenthesis. fields[0] is field 3 (state).
+    let malformed = || io::Error::new(ErrorKind::InvalidData, "Malformed /proc/PID/stat");
+    let comm_start = stat_content.find('(').ok_or_else(malformed)?;
+    let comm_end = stat_content.rfind(')').ok_or_else(malformed)?;
+    let comm = stat_content
+        .get(comm_start + 1..comm_end)
+        .ok_or_else(malformed)?
+        .to_string();
+    let after_comm = &stat_content[comm_end + 1..];
+    let fields: Vec<&str> = after_comm.split_whitespace().collect();
+
+    let get_field = |field_number: usize| -> io::Result<&str> {
+        fields.get(field_number - 3).copied().ok_or_else(|| {
+            io::Error::new(
+                ErrorKind::NotFound,
+                format!("Could not find field {} in /proc/PID/stat", field_number),
+            )
+        })
+    };
+    let invalid_field = |field_number: usize| {
+        io::Error::new(
+            ErrorKind::InvalidData,
+            format!("Failed to parse field {} of /proc/PID/stat", field_number),
+        )
+    };
+    let parse_field = |field_number: usize| -> io::Result<u64> {
+        get_field(field_number)?
+            .parse::<u64>()
+            .map_err(|_| invalid_field(field_number))
+    };
+    // Priority and nice are negative for real-time and high-priority tasks
+    let parse_signed_field = |field_number: usize| -> io::Result<i64> {
+        get_field(field_number)?
+            .parse::<i64>()
+            .map_err(|_| invalid_field(field_number))
+    };
+
+    Ok(TaskStat {
+        comm,
+        state: get_field(3)?.chars().next().unwrap_or('?'),
+        ppid: parse_field(4)? as u32,
+        flags: parse_field(9)?,
+        cpu_time_ticks: parse_field(14)? + parse_field(15)?,
+        priority: parse_signed_field(18)?,
+        nice: parse_signed_field(19)?,
+        start_time_ticks: parse_field(22)?,
+        rss_pages: parse_field(24)?,
+        cpu: parse_field(39)? as u32,
+        policy: parse_field(41)? as u32,
+    })
+}
+
+// USER_HZ, the unit of utime/stime in /proc/PID/stat
+pub fn clock_ticks_per_sec() -> f64 {
+    // SAFETY: sysconf has no preconditions
+    let ticks = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+    if ticks > 0 { ticks as f64 } else { 100.0 }
+}
+
+// Unit of the RSS field in /proc/PID/stat
+fn page_size_kb() -> u64 {
+    // SAFETY: sysconf has no preconditions
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    if page_size > 0 {
+        page_size as u64 / 1024
+    } else {
+        4
+    }
+}
+
+// "btime" from /proc/stat: boot time in seconds since the epoch, read once
+fn boot_time() -> u64 {
+    static BOOT_TIME: OnceLock<u64> = OnceLock::new();
+    *BOOT_TIME.get_or_init(|| {
+        fs::read_to_string("/proc/stat")
+            .unwrap_or_default()
+            .lines()
+            .find_map(|line| line.strip_prefix("btime ")?.trim().parse().ok())
+            .unwrap_or(0)
+    })
+}
+
+// include/uapi/linux/sched.h, abbreviated like ps's CLS column
+pub fn sched_policy_name(policy: u32) -> &'static str {
+    match policy {
+        0 => "TS",  // SCHED_OTHER
+        1 => "FF",  // SCHED_FIFO
+        2 => "RR",  // SCHED_RR
+        3 => "B",   // SCHED_BATCH
+        5 => "IDL", // SCHED_IDLE
+        6 => "DLN", // SCHED_DEADLINE
+        _ => "?",
+    }
+}
+
+// Task state letters of /proc/PID/stat, as listed in proc(5)
+pub fn describe_task_state(state: char) -> &'static str {
+    match state {
+        'R' => "running",
+        'S' => "sleeping",
+        'D' => "uninterruptible sleep",
+        'T' => "stopped",
+        't' => "tracing stop",
+        'Z' => "zombie",
+        'X' => "dead",
+        'I' => "idle",
+        _ => "unknown",
+    }
+}
+
+// Stat of every thread on the system as (PID, TID, stat), for per-tick tracking
+pub fn get_all_task_stats() -> io::Result<Vec<(u32, u32, TaskStat)>> {
+    let mut tasks = Vec::new();
+
+    for entry in fs::read_dir("/proc")?.filter_map(Result::ok) {
+        if let Some(dir_name) = entry.file_name().to_str()
+            && let Ok(pid) = dir_name.parse::<u32>()
+            && let Ok(task_entries) = fs::read_dir(format!("/proc/{}/task", pid))
+        {
+            for task_entry in task_entries.filter_map(Result::ok) {
+                if let Some(tid_str) = task_entry.file_name().to_str()
+                    && let Ok(tid) = tid_str.parse::<u32>()
+                    && let Ok(stat) = parse_task_stat(pid, tid)
+                {
+                    tasks.push((pid, tid, stat));
+                }
+            }
+        }
+    }
+
+    Ok(tasks)
+}
@@ -0,0 +1,164 @@
+use crate::numa_node::NumaNode;
+use crate::proc_info::ProcessNumaMemory;
+use std::collections::{BTreeMap, HashMap};
+
+// Smaller processes gain too little from a move to be worth listing
+const MIN_SUGGESTION_MEMORY_KB: u64 = 64 * 1024;
+// Locality must improve by at least this share of the process's memory
+const MIN_LOCALITY_GAIN: f64 = 0.2;
+// Nodes this busy don't get more threads moved onto them
+const BUSY_NODE_UTILIZATION: f64 = 80.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Move {
+    Cpus { to: u32 },   // Pin every thread to the CPUs of this node
+    Memory { to: u32 }, // Migrate the memory on every other node to this one
+}
+
+#[derive(Debug, Clone)]
+pub struct Suggestion {
+    pub pid: u32,
+    pub name: String,
+    pub user: String,
+    pub cmdline: String,
+    pub action: Move,
+    pub total_kb: u64,
+    pub locality_now: f64, // Share of memory on the nodes its threads run on, 0..1
+    pub locality_after: f64, // The same once the move is applied
+    pub reason: String,    // "92% of its memory lives", completed by describe()
+}
+
+impl Suggestion {
+    // Memory that would become local to the threads using it
+    pub fn gained_local_kb(&self) -> u64 {
+        ((self.locality_after - self.locality_now).max(0.0) * self.total_kb as f64) as u64
+    }
+
+    // The fields the '/' filter matches, those of the process's main thread
+    pub fn filter_fields(&self) -> [String; 4] {
+        [
+            self.pid.to_string(),
+            self.name.clone(),
+            self.user.clone(),
+            self.cmdline.clone(),
+        ]
+    }
+
+    // "move PID 1234 (java) CPUs to N1 where 92% of its memory lives"
+    pub fn describe(&self) -> String {
+        match self.action {
+            Move::Cpus { to } => format!(
+                "move PID {} ({}) CPUs to N{} where {}",
+                self.pid, self.name, to, self.reason
+            ),
+            Move::Memory { to } => format!(
+                "move PID {} ({}) memory to N{} where {}",
+                self.pid, self.name, to, self.reason
+            ),
+        }
+    }
+}
+
+// Compare where each process's threads run with where its memory lives, and suggest the
+// move that makes most of its memory local: its CPUs to the memory unless that node is
+// busy, or its memory to the CPUs if it fits. Largest gain in local memory first.
+pub fn suggest_moves(
+    processes: &[ProcessNumaMemory],
+    thread_nodes: &HashMap<u32, BTreeMap<u32, usize>>, // PID -> threads running on each node
+    nodes: &[NumaNode],
+) -> Vec<Suggestion> {
+    let node_utilization: HashMap<u32, f64> = nodes
+        .iter()
+        .filter_map(|node| {
+            let cpus = node.cpus.as_ref().filter(|cpus| !cpus.is_empty())?;
+            let total: f64 = cpus.iter().map(|cpu| cpu.utilization).sum();
+            Some((node.id, total / cpus.len() as f64))
+        })
+        .collect();
+    let node_free_kb: HashMap<u32, u64> = nodes
+        .iter()
+        .map(|node| {
+            let free_mb = node.total_memory_mb.saturating_sub(node.used_memory_mb);
+            (node.id, free_mb * 1024)
+        })
+        .collect();
+
+    let mut suggestions: Vec<Suggestion> = processes
+        .iter()
+        .filter(|process| !process.info.is_kernel_thread)
+        .filter_map(|process| {
+            let total_kb = process.total_kb();
+            if total_kb < MIN_SUGGESTION_MEMORY_KB {
+                return None;
+            }
+            let threads = thread_nodes.get(&process.info.tgid)?;
+            let thread_count: usize = threads.values().sum();
+            if thread_count == 0 {
+                return None;
+            }
+
+            let memory_share = |node: u32| {
+                process.node_memory_kb.get(&node).copied().unwrap_or(0) as f64 / total_kb as f64
+            };
+            let thread_share =
+                |node: u32| threads.get(&node).copied().unwrap_or(0) as f64 / thread_count as f64;
+            // Chance that a thread finds a page on its own node
+            let locality_now: f64 = threads
+                .keys()
+                .map(|n| thread_share(*n) * memory_share(*n))
+                .sum();
+
+            // Threads follow the memory: all of it on the home node becomes local
+            let cpu_move = process.home_node().and_then(|home| {
+                let utilization = *node_utilization.get(&home)?;
+                (utilization < BUSY_NODE_UTILIZATION).then(|| Suggestion {
+                    pid: process.info.tgid,
+                    name: process.info.process_name.clone(),
+                    user: process.info.user.clone(),
+                    cmdline: process.info.cmdline.clone(),
+                    action: Move::Cpus { to: home },
+                    total_kb,
+                    locality_now,
+                    locality_after: memory_share(home),
+                    reason: format!("{:.0}% of its memory lives", memory_share(home) * 100.0),
+                })
+            });
+
+            // Memory follows the threads: the share of threads on that node sees local memory
+            let cpu_node = threads
+                .iter()
+                .max_by_key(|(_, count)| **count)
+                .map(|(node, _)| *node);
+            let memory_move = cpu_node.and_then(|node| {
+                let to_move_kb = total_kb - process.node_memory_kb.get(&node).copied().unwrap_or(0);
+                (to_move_kb <= node_free_kb.get(&node).copied().unwrap_or(0)).then(|| Suggestion {
+                    pid: process.info.tgid,
+                    name: process.info.process_name.clone(),
+                    user: process.info.user.clone(),
+                    cmdline: process.info.cmdline.clone(),
+                    action: Move::Memory { to: node },
+                    total_kb,
+                    locality_now,
+                    locality_after: thread_share(node),
+                    reason: format!("{:.0}% of its threads run", thread_share(node) * 100.0),
+                })
+            });
+
+            // Moving CPUs is instant while migrating memory copies pages, so CPUs win ties
+            [cpu_move, memory_move]
+                .into_iter()
+                .flatten()
+                .filter(|s| s.locality_after - s.locality_now >= MIN_LOCALITY_GAIN)
+                .reduce(|best, s| {
+                    if s.locality_after > best.locality_after {
+                        s
+                    } else {
+                        best
+                    }
+                })
+        })
+        .collect();
+
+    suggestions.sort_by_key(|s| std::cmp::Reverse(s.gained_local_kb()));
+    suggestions
+}
@@ -1,4 +1,4 @@
-use crate::numa_node::{CpuCore, NumaNode};
+use crate::numa_node::{CpuCore, NodeNumaStat, NumaNode};
 use std::{
     error::Error,
     fs,
@@ -12,49 +12,57 @@ pub fn get_numa_node_data() -> Result<Vec<NumaNode>, Box<dyn Error>> {
     for entry in fs::read_dir(node_base_path)? {
         let path = entry?.path();
 
-        if path.is_dir() {
-            if let Some(name_osstr) = path.file_name() {
-                let name = name_osstr.to_string_lossy();
-                if name.starts_with("node") {
-                    if let Ok(id) = name[4..].parse::<u32>() {
-                        // Memory Info
-                        let meminfo_path = path.join("meminfo");
-                        let (total_mb, used_mb) =
-                            parse_node_meminfo(&meminfo_path).unwrap_or_else(|e| {
-                                eprintln!("Failed to parse meminfo for node {}: {}", id, e);
-                                (0, 0)
-                            });
-
-                        // CPU Info
-                        let cpulist_path = path.join("cpulist");
-                        let mut node_cpus: Option<Vec<CpuCore>> = None;
-
-                        if cpulist_path.exists() {
-                            let cpulist_str = fs::read_to_string(cpulist_path)?;
-                            if !cpulist_str.trim().is_empty() {
-                                let core_ids = parse_cpulist(&cpulist_str);
-                                if !core_ids.is_empty() {
-                                    node_cpus = Some(
-                                        core_ids
-                                            .into_iter()
-                                            .map(|core_id| CpuCore {
-                                                id: core_id,
-                                                ..Default::default()
-                                            })
-                                            .collect(),
-                                    );
-                                }
-                            }
-                        }
+        if path.is_dir()
+            && let Some(name_osstr) = path.file_name()
+        {
+            let name = name_osstr.to_string_lossy();
+            if name.starts_with("node")
+                && let Ok(id) = name[4..].parse::<u32>()
+            {
+                // Memory Info
+                let meminfo_path = path.join("meminfo");
+                let (total_mb, used_mb) = parse_node_meminfo(&meminfo_path).unwrap_or_else(|e| {
+                    eprintln!("Failed to parse meminfo for node {}: {}", id, e);
+                    (0, 0)
+                });
+
+                // CPU Info
+                let cpulist_path = path.join("cpulist");
+                let mut node_cpus: Option<Vec<CpuCore>> = None;
 
-                        nodes_info.push(NumaNode {
-                            id,
-                            cpus: node_cpus,
-                            total_memory_mb: total_mb,
-                            used_memory_mb: used_mb,
-                        });
+                if cpulist_path.exists() {
+                    let cpulist_str = fs::read_to_string(cpulist_path)?;
+                    if !cpulist_str.trim().is_empty() {
+                        let core_ids = parse_cpulist(&cpulist_str);
+                        if !core_ids.is_empty() {
+                            node_cpus = Some(
+                                core_ids
+                                    .into_iter()
+                                    .map(|core_id| CpuCore {
+                                        id: core_id,
+                                        ..Default::default()
+                                    })
+                                    .collect(),
+                            );
+                        }
                     }
                 }
+
+                // Allocation counters, turned into rates by the caller
+                let numastat = parse_node_numastat(&path.join("numastat"))
+                    .map_err(|e| eprintln!("Failed to parse numastat for node {}: {}", id, e))
+                    .ok();
+
+                nodes_info.push(NumaNode {
+                    id,
+                    cpus: node_cpus,
+                    total_memory_mb: total_mb,
+                    used_memory_mb: used_mb,
+                    io_devices: Vec::new(),
+                    numastat,
+                    local_alloc_percent: None,
+                    remote_allocs_per_sec: 0.0,
+                });
             }
         }
     }
@@ -97,19 +105,33 @@ fn parse_node_meminfo(path: &std::path::Path) -> Result<(u64, u64), Box<dyn Erro
     Ok((total_kb / 1024, (used_kb - inactive_kb) / 1024)) // Convert KB to MB
 }
 
+fn parse_node_numastat(path: &std::path::Path) -> Result<NodeNumaStat, Box<dyn Error>> {
+    let mut stat = NodeNumaStat::default();
+    for line in fs::read_to_string(path)?.lines() {
+        let mut parts = line.split_whitespace(); // local_node 123
+        let key = parts.next().unwrap_or("");
+        let value = parts.next().unwrap_or("0").parse::<u64>().unwrap_or(0);
+        match key {
+            "local_node" => stat.local_node = value,
+            "other_node" => stat.other_node = value,
+            _ => {}
+        }
+    }
+    Ok(stat)
+}
+
 // Basic parser for cpulist format like "0-3,7,10-11"
-fn parse_cpulist(cpulist_str: &str) -> Vec<u32> {
+pub fn parse_cpulist(cpulist_str: &str) -> Vec<u32> {
     let mut cpus = Vec::new();
     for part in cpulist_str.trim().split(',') {
         if part.contains('-') {
             let range_parts: Vec<&str> = part.split('-').collect();
-            if range_parts.len() == 2 {
-                if let (Ok(start), Ok(end)) =
+            if range_parts.len() == 2
+                && let (Ok(start), Ok(end)) =
                     (range_parts[0].parse::<u32>(), range_parts[1].parse::<u32>())
-                {
-                    for cpu_id in start..=end {
-                        cpus.push(cpu_id);
-                    }
+            {
+                for cpu_id in start..=end {
+                    cpus.push(cpu_id);
                 }
             }
         } else {
@@ -122,8 +144,107 @@ fn parse_cpulist(cpulist_str: &str) -> Vec<u32> {
     cpus
 }
 
+// parse_cpulist for typed input: rejects malformed parts and any id above max_id before
+// expanding a range, so "0-4000000000" is an error instead of a 16 GB Vec
+pub fn parse_cpulist_bounded(list: &str, max_id: u32) -> Result<Vec<u32>, String> {
+    let mut ids = Vec::new();
+    for part in list.trim().split(',') {
+        let (start, end) = match part.split_once('-') {
+            Some((start, end)) => (start.parse::<u32>(), end.parse::<u32>()),
+            None => (part.parse::<u32>(), part.parse::<u32>()),
+        };
+        let (Ok(start), Ok(end)) = (start, end) else {
+            return Err(format!("\"{}\" is not a number or range", part));
+        };
+        if start > end {
+            return Err(format!("\"{}\" is a backwards range", part));
+        }
+        if end > max_id {
+            return Err(format!("{} is above the highest, {}", end, max_id));
+        }
+        ids.extend(start..=end);
+    }
+    ids.sort();
+    ids.dedup();
+    Ok(ids)
+}
+
+// Inverse of parse_cpulist: [0, 1, 2, 3, 7] -> "0-3,7"
+pub fn format_cpulist(ids: &[u32]) -> String {
+    let mut ranges: Vec<(u32, u32)> = Vec::new();
+    for &id in ids {
+        match ranges.last_mut() {
+            Some((_, end)) if end.checked_add(1) == Some(id) => *end = id,
+            _ => ranges.push((id, id)),
+        }
+    }
+    ranges
+        .iter()
+        .map(|(start, end)| {
+            if start == end {
+                start.to_string()
+            } else {
+                format!("{}-{}", start, end)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
 pub fn get_all_present_cpu_indices() -> Result<Vec<u32>, Box<dyn Error>> {
     let cpulist_str = fs::read_to_string("/sys/devices/system/cpu/present")?;
     let cpu_indices = parse_cpulist(&cpulist_str);
     Ok(cpu_indices)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_cpulist_expands_ranges() {
+        assert_eq!(parse_cpulist("0-3,7,10-11\n"), [0, 1, 2, 3, 7, 10, 11]);
+        assert_eq!(parse_cpulist("5"), [5]);
+        assert!(parse_cpulist("").is_empty());
+    }
+
+    #[test]
+    fn parse_cpulist_bounded_accepts_lists_up_to_the_limit() {
+        assert_eq!(parse_cpulist_bounded("0-3,8", 15), Ok(vec![0, 1, 2, 3, 8]));
+        assert_eq!(parse_cpulist_bounded(" 3 ", 3), Ok(vec![3]));
+        // Sorted, each ID once
+        assert_eq!(parse_cpulist_bounded("2,0-2,1", 3), Ok(vec![0, 1, 2]));
+    }
+
+    #[test]
+    fn parse_cpulist_bounded_rejects_huge_ranges_before_expanding() {
+        assert!(parse_cpulist_bounded("0-4000000000", 63).is_err());
+        assert!(parse_cpulist_bounded("0-4294967295", 63).is_err());
+        assert!(parse_cpulist_bounded("64", 63).is_err());
+    }
+
+    #[test]
+    fn parse_cpulist_bounded_rejects_malformed_input() {
+        for input in ["", "a", "1-", "-1", "1-2-3", "1,,2", "3-1", "0-99999999999"] {
+            assert!(parse_cpulist_bounded(input, 63).is_err(), "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn format_cpulist_collapses_ranges() {
+        assert_eq!(format_cpulist(&[0, 1, 2, 3, 7, 10, 11]), "0-3,7,10-11");
+        assert_eq!(format_cpulist(&[4]), "4");
+        assert_eq!(format_cpulist(&[]), "");
+        assert_eq!(
+            format_cpulist(&[u32::MAX - 1, u32::MAX]),
+            "4294967294-4294967295"
+        );
+    }
+
+    #[test]
+    fn format_cpulist_round_trips_through_parse_cpulist() {
+        for list in ["0", "0-3,7,10-11", "1,3,5", "0-127"] {
+            assert_eq!(format_cpulist(&parse_cpulist(list)), list);
+        }
+    }
+}
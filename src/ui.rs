@@ -1,50 +1,170 @@
-use crate::app::App;
+use crate::app::{
+    ActionKind, App, ChartMetric, DETAIL_TIMELINE_TICKS, DialogStage, InputMode, PopupColumn,
+    PopupRow, PopupSortKey, PopupTarget, TaskChange, View,
+};
+use crate::numa_balancing::{NumaSchedStats, describe_numa_balancing_mode};
+use crate::numa_maps::{MappingBacking, NumaMapping, dominant_policy, get_node_memory_kb};
+use crate::numa_node::{IoDevice, IoDeviceKind};
+use crate::proc_info::{ProcessInfo, sched_policy_name};
+use crate::sys_numa_info::format_cpulist;
+use std::collections::{BTreeMap, HashMap, VecDeque};
 
 use ratatui::{
     Frame,
     layout::{Constraint, Direction, Flex, Layout, Rect},
-    style::{Color, Style},
+    style::{Color, Modifier, Style},
+    symbols::Marker,
     text::{Line, Span},
-    widgets::{Block, Borders, Clear, Gauge, List, ListItem, Paragraph},
+    widgets::{
+        Axis, Block, Borders, Cell, Chart, Clear, Dataset, Gauge, GraphType, List, ListItem,
+        Paragraph, Row, Table, Wrap,
+    },
 };
 
+const MAX_IO_DEVICE_LINES: usize = 4; // Per-device lines shown below the node totals
+const PLACEMENT_BAR_WIDTH: usize = 20;
+const CORE_SPARKLINE_WIDTH: usize = 12; // Cap for the sparkline after each core
+const SPARKLINE_LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
 pub fn draw(app: &mut App, frame: &mut Frame) {
-    // Clear CPU core areas at the start of each draw
-    app.clear_cpu_core_areas();
+    // Clear clickable areas at the start of each draw
+    app.clear_click_areas();
 
+    // Reserve a header line while actions are restricted, and bottom lines for the '/' filter
+    // while it is being typed or applied, and for the outcome of the last action
+    let show_header = app.read_only || app.dry_run;
+    let show_filter = app.input_mode == InputMode::Search || !app.filter.pattern.is_empty();
+    let [header_area, area, filter_area, migration_area, status_area] = Layout::vertical([
+        Constraint::Length(show_header as u16),
+        Constraint::Min(0),
+        Constraint::Length(show_filter as u16),
+        Constraint::Length(app.memory_migration.is_some() as u16),
+        Constraint::Length(app.status.is_some() as u16),
+    ])
+    .areas(frame.area());
+    if show_header {
+        render_mode_header(frame, app, header_area);
+    }
+    if show_filter {
+        render_filter_bar(frame, app, filter_area);
+    }
+    if let Some(migration) = &app.memory_migration {
+        let moved_kb = migration.initial_kb.saturating_sub(migration.remaining_kb);
+        frame.render_widget(
+            Gauge::default()
+                .gauge_style(Style::default().fg(Color::Magenta))
+                .ratio(migration.progress())
+                .label(format!(
+                    "Migrating {} N{} → N{}: {:.1} of {:.1} MiB moved ({:.0}s)",
+                    migration.target.describe(),
+                    format_cpulist(&migration.from),
+                    format_cpulist(&migration.to),
+                    moved_kb as f64 / 1024.0,
+                    migration.initial_kb as f64 / 1024.0,
+                    migration.started.elapsed().as_secs_f64()
+                )),
+            migration_area,
+        );
+    }
+    if let Some(status) = &app.status {
+        frame.render_widget(
+            Paragraph::new(status.text.clone()).style(Style::default().fg(if status.is_error {
+                Color::Red
+            } else {
+                Color::Green
+            })),
+            status_area,
+        );
+    }
+
+    match app.view {
+        View::Processes => render_process_table(frame, app, area),
+        View::ProcessDetail => render_process_detail(frame, app, area),
+        View::Migrations => render_migration_table(frame, app, area),
+        View::Suggestions => render_suggestion_table(frame, app, area),
+        View::Chart => render_chart_view(frame, app, area),
+        View::Nodes => render_node_view(frame, app, area),
+    }
+
+    // Action dialogs go on top of every view
+    if app.action_dialog.is_some() {
+        render_action_dialog(frame, app, area);
+    }
+}
+
+fn render_node_view(frame: &mut Frame, app: &mut App, area: Rect) {
     let num_nodes = app.numa_nodes.len();
     if num_nodes == 0 {
         frame.render_widget(
             Paragraph::new("No NUMA nodes found or error fetching data.")
                 .block(Block::default().title("NUMA Monitor").borders(Borders::ALL)),
-            frame.area(),
+            area,
         );
         return;
     }
 
+    // Commands started with 'r' get a panel below the nodes: borders, header and a row each
+    let launched_height = if app.launched.is_empty() {
+        0
+    } else {
+        app.launched.len() as u16 + 3
+    };
+    let [nodes_area, launched_area] =
+        Layout::vertical([Constraint::Min(0), Constraint::Length(launched_height)]).areas(area);
+    if launched_height > 0 {
+        render_launched_panel(frame, app, launched_area);
+    }
+
     // Create a layout with one column per NUMA node
     // TODO: add layouts.toml file under config/ to allow configuration of runtime layouts
     let constraints: Vec<Constraint> =
-        std::iter::repeat(Constraint::Percentage(100 / num_nodes as u16))
-            .take(num_nodes)
-            .collect();
+        std::iter::repeat_n(Constraint::Percentage(100 / num_nodes as u16), num_nodes).collect();
 
-    let node_chunks = Layout::horizontal(constraints).split(frame.area());
+    let node_chunks = Layout::horizontal(constraints).split(nodes_area);
 
-    // Collect all CPU core areas before adding them to app
+    // Collect all CPU core and node areas before adding them to app
     let mut all_cpu_core_areas = Vec::new();
+    let mut all_node_areas = Vec::new();
 
     for (i, node_data) in app.numa_nodes.iter().enumerate() {
         let node_chunk = node_chunks[i];
+        // The node cursor, which the run prompt binds to
+        let border_style = if node_data.id == app.selected_node {
+            Style::default().fg(Color::Cyan)
+        } else {
+            Style::default()
+        };
         let node_block = Block::default()
             .title(format!("NUMA Node {}", node_data.id))
-            .borders(Borders::ALL);
+            .borders(Borders::ALL)
+            .border_style(border_style);
         frame.render_widget(node_block, node_chunk);
 
+        // The title row opens the node popup
+        all_node_areas.push((
+            node_data.id,
+            Rect {
+                height: 1,
+                ..node_chunk
+            },
+        ));
+
+        // Title + one line per device, but always at least room for the totals
+        let shown_io_devices = node_data.io_devices.len().min(MAX_IO_DEVICE_LINES);
+        let io_height = 1 + 2 + shown_io_devices as u16;
+
         let inner_chunks = Layout::default()
             .direction(Direction::Vertical)
             .margin(1)
-            .constraints([Constraint::Percentage(70), Constraint::Percentage(30)].as_ref())
+            .constraints(
+                [
+                    Constraint::Min(0),
+                    Constraint::Length(io_height),
+                    Constraint::Length(5), // History title + one line per metric
+                    Constraint::Percentage(30),
+                ]
+                .as_ref(),
+            )
             .split(node_chunk);
 
         // --- CPU Utilization Section ---
@@ -90,15 +210,7 @@ pub fn draw(app: &mut App, frame: &mut Frame) {
                         .iter()
                         .enumerate()
                         .map(|(item_idx, cpu)| {
-                            let util_color = if cpu.utilization > 85.0 {
-                                Color::Red
-                            } else if cpu.utilization > 65.0 {
-                                Color::Yellow
-                            } else if cpu.utilization > 30.0 {
-                                Color::Green
-                            } else {
-                                Color::Blue
-                            };
+                            let util_color = utilization_color(cpu.utilization);
 
                             // Calculate the area for this CPU core item
                             let column_area = column_chunks[col];
@@ -113,12 +225,25 @@ pub fn draw(app: &mut App, frame: &mut Frame) {
                             // Store CPU core area for later registration
                             node_cpu_core_areas.push((cpu.id, item_area));
 
+                            let label = format!("Core {}: ", cpu.id);
+                            let value = format!("{:.1}%", cpu.utilization);
+                            // Recent utilization after the value, if the column has room
+                            let spark_width = (column_area.width as usize)
+                                .saturating_sub(label.len() + 7)
+                                .min(CORE_SPARKLINE_WIDTH);
+                            let spark = app
+                                .history
+                                .cores
+                                .get(&cpu.id)
+                                .map(|history| sparkline(history.samples(), 100.0, spark_width))
+                                .unwrap_or_default();
                             let line = Line::from(vec![
-                                Span::raw(format!("Core {}: ", cpu.id)),
+                                Span::raw(label),
                                 Span::styled(
-                                    format!("{:.1}%", cpu.utilization),
+                                    format!("{:<7}", value),
                                     Style::default().fg(util_color),
                                 ),
+                                Span::styled(spark, Style::default().fg(Color::DarkGray)),
                             ]);
 
                             ListItem::new(line)
@@ -148,8 +273,14 @@ pub fn draw(app: &mut App, frame: &mut Frame) {
             );
         }
 
+        // --- I/O Throughput Section ---
+        render_node_io(frame, &node_data.io_devices, inner_chunks[1]);
+
+        // --- History Section ---
+        render_node_history(frame, app, node_data.id, inner_chunks[2]);
+
         // --- Memory Utilization Section ---
-        let memory_area = inner_chunks[1];
+        let memory_area = inner_chunks[3];
 
         let memory_ratio = if node_data.total_memory_mb > 0 {
             node_data.used_memory_mb as f64 / node_data.total_memory_mb as f64
@@ -177,77 +308,1622 @@ pub fn draw(app: &mut App, frame: &mut Frame) {
                 Style::default().fg(gauge_color).bg(Color::Black), // Background of the unfilled part
                                                                    // .add_modifier(Modifier::ITALIC), // Optional
             )
-            .ratio(memory_ratio.min(1.0).max(0.0)) // Clamp ratio between 0 and 1
+            .ratio(memory_ratio.clamp(0.0, 1.0)) // Clamp ratio between 0 and 1
             .label(memory_label);
         frame.render_widget(memory_gauge, memory_area);
+
+        // So does the memory gauge
+        all_node_areas.push((node_data.id, memory_area));
     }
 
-    // Register all CPU core areas after the loop completes
+    // Register all CPU core and node areas after the loop completes
     for (cpu_id, area) in all_cpu_core_areas {
         app.add_cpu_core_area(cpu_id, area);
     }
+    for (node_id, area) in all_node_areas {
+        app.add_node_area(node_id, area);
+    }
 
     // Render popup if it should be shown
     if app.popup_state.show {
-        render_process_popup(frame, app);
+        render_process_popup(frame, app, area);
+    }
+}
+
+// CPU, memory and allocation locality of a node over the retention window
+fn render_node_history(frame: &mut Frame, app: &App, node_id: u32, area: Rect) {
+    let mut lines = vec![Line::from(format!(
+        "History (last {})",
+        format_window(app.history_retention.as_secs_f64())
+    ))];
+
+    if let Some(history) = app.history.nodes.get(&node_id) {
+        // Label, latest value, sparkline
+        let spark_width = (area.width as usize).saturating_sub(7 + 11);
+        let metrics = [
+            ("CPU", &history.cpu_utilization, 100.0, "%"),
+            ("Mem", &history.memory_used_percent, 100.0, "%"),
+            ("Local", &history.local_alloc_percent, 100.0, "%"),
+            (
+                "Remote",
+                &history.remote_allocs_per_sec,
+                history.remote_allocs_per_sec.max(),
+                " pg/s",
+            ),
+        ];
+        for (label, samples, max, unit) in metrics {
+            let latest = samples.latest().unwrap_or(0.0);
+            let color = match label {
+                "CPU" => utilization_color(latest),
+                "Local" if latest < 50.0 => Color::Red,
+                "Remote" if latest > 0.0 => Color::Yellow,
+                _ => Color::Green,
+            };
+            lines.push(Line::from(vec![
+                Span::raw(format!("{:<7}", label)),
+                Span::raw(format!("{:<11}", format!("{:.0}{}", latest, unit))),
+                Span::styled(
+                    sparkline(samples.samples(), max, spark_width),
+                    Style::default().fg(color),
+                ),
+            ]));
+        }
+    }
+
+    frame.render_widget(Paragraph::new(lines), area);
+}
+
+// "5m", "2m30s", "45s"
+fn format_window(secs: f64) -> String {
+    let secs = secs.round() as u64;
+    match (secs / 60, secs % 60) {
+        (0, secs) => format!("{}s", secs),
+        (mins, 0) => format!("{}m", mins),
+        (mins, secs) => format!("{}m{}s", mins, secs),
+    }
+}
+
+fn render_chart_view(frame: &mut Frame, app: &App, area: Rect) {
+    let tick_secs = crate::TICK_RATE.as_secs_f64();
+    let window_secs = app.chart.window as f64 * tick_secs;
+    let block = Block::default()
+        .title(format!(
+            "Node Metrics over the last {} (1-3 charts, Left/Right + Space nodes, +/- zoom, c or ESC to go back)",
+            format_window(window_secs)
+        ))
+        .borders(Borders::ALL);
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    let [legend_area, charts_area] =
+        Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(inner);
+
+    // Every node with its line color, hidden ones crossed out, the cursor reversed
+    let legend: Vec<Span> = app
+        .numa_nodes
+        .iter()
+        .map(|node| {
+            let mut style = if app.chart.hidden_nodes.contains(&node.id) {
+                Style::default()
+                    .fg(Color::DarkGray)
+                    .add_modifier(Modifier::CROSSED_OUT)
+            } else {
+                Style::default().fg(node_color(node.id))
+            };
+            if node.id == app.selected_node {
+                style = style.add_modifier(Modifier::REVERSED);
+            }
+            Span::styled(format!(" N{} ", node.id), style)
+        })
+        .collect();
+    frame.render_widget(Paragraph::new(Line::from(legend)), legend_area);
+
+    if app.chart.metrics.is_empty() {
+        frame.render_widget(
+            Paragraph::new("All charts are hidden, 1-3 shows them again")
+                .style(Style::default().fg(Color::Yellow)),
+            charts_area,
+        );
+        return;
+    }
+    let chart_areas = Layout::vertical(vec![
+        Constraint::Ratio(1, app.chart.metrics.len() as u32);
+        app.chart.metrics.len()
+    ])
+    .split(charts_area);
+
+    for (metric, chart_area) in app.chart.metrics.iter().zip(chart_areas.iter()) {
+        // The newest sample sits at 0, older ones at negative seconds
+        let series: Vec<(u32, Vec<(f64, f64)>)> = app
+            .history
+            .nodes
+            .iter()
+            .filter(|(node_id, _)| !app.chart.hidden_nodes.contains(node_id))
+            .map(|(node_id, history)| {
+                let samples = match metric {
+                    ChartMetric::Cpu => history.cpu_utilization.samples(),
+                    ChartMetric::Memory => history.memory_used_percent.samples(),
+                    ChartMetric::RemoteAllocs => history.remote_allocs_per_sec.samples(),
+                };
+                let shown = samples.len().min(app.chart.window);
+                let points = samples
+                    .iter()
+                    .skip(samples.len() - shown)
+                    .enumerate()
+                    .map(|(i, value)| (-((shown - 1 - i) as f64) * tick_secs, *value))
+                    .collect();
+                (*node_id, points)
+            })
+            .collect();
+
+        let y_max = match metric {
+            ChartMetric::Cpu | ChartMetric::Memory => 100.0,
+            ChartMetric::RemoteAllocs => series
+                .iter()
+                .flat_map(|(_, points)| points.iter().map(|(_, y)| *y))
+                .fold(1.0, f64::max),
+        };
+        // Small rates need a decimal to tell the labels apart
+        let precision = if y_max < 10.0 { 1 } else { 0 };
+        let datasets: Vec<Dataset> = series
+            .iter()
+            .map(|(node_id, points)| {
+                Dataset::default()
+                    .name(format!("N{}", node_id))
+                    .marker(Marker::Braille)
+                    .graph_type(GraphType::Line)
+                    .style(Style::default().fg(node_color(*node_id)))
+                    .data(points)
+            })
+            .collect();
+
+        let axis_style = Style::default().fg(Color::Gray);
+        let chart = Chart::new(datasets)
+            .block(Block::default().title(metric.label()))
+            .x_axis(
+                Axis::default()
+                    .style(axis_style)
+                    .bounds([-window_secs, 0.0])
+                    .labels([
+                        format!("-{}", format_window(window_secs)),
+                        format!("-{}", format_window(window_secs / 2.0)),
+                        "now".to_string(),
+                    ]),
+            )
+            .y_axis(
+                Axis::default()
+                    .style(axis_style)
+                    .bounds([0.0, y_max])
+                    .labels([
+                        "0".to_string(),
+                        format!("{:.*}", precision, y_max / 2.0),
+                        format!("{:.*}", precision, y_max),
+                    ]),
+            );
+        frame.render_widget(chart, *chart_area);
     }
 }
 
-fn render_process_popup(frame: &mut Frame, app: &App) {
+// Squeeze the samples into at most `width` cells scaled to `max`. Each cell shows the peak
+// of the samples it covers, so a short spike stays visible in a long window.
+fn sparkline(samples: &VecDeque<f64>, max: f64, width: usize) -> String {
+    let cells = width.min(samples.len());
+    (0..cells)
+        .map(|cell| {
+            let start = cell * samples.len() / cells;
+            let end = ((cell + 1) * samples.len() / cells).max(start + 1);
+            let peak = samples.range(start..end).copied().fold(0.0, f64::max);
+            let level = if max > 0.0 {
+                (peak / max * (SPARKLINE_LEVELS.len() - 1) as f64).round() as usize
+            } else {
+                0
+            };
+            SPARKLINE_LEVELS[level.min(SPARKLINE_LEVELS.len() - 1)]
+        })
+        .collect()
+}
+
+fn render_launched_panel(frame: &mut Frame, app: &App, area: Rect) {
+    let header = Row::new(vec![
+        "PID", "Node", "State", "CPU", "On", "Memory", "Local", "Command",
+    ])
+    .style(Style::default().add_modifier(Modifier::BOLD));
+
+    let cpu_nodes = app.cpu_node_map();
+    let rows: Vec<Row> = app
+        .launched
+        .iter()
+        .map(|launched| {
+            let current_node = launched
+                .info
+                .as_ref()
+                .and_then(|info| cpu_nodes.get(&info.cpu).copied());
+            let total_kb: u64 = launched.node_memory_kb.values().sum();
+            let local_kb = launched
+                .node_memory_kb
+                .get(&launched.node)
+                .copied()
+                .unwrap_or(0);
+            let cpu = launched
+                .info
+                .as_ref()
+                .map(|info| info.cpu.to_string())
+                .unwrap_or_else(|| "-".to_string());
+            // Running off the bound node means the binding didn't take
+            let off_node = current_node != Some(launched.node);
+            let memory = launched
+                .node_memory_kb
+                .iter()
+                .map(|(node, kb)| format!("N{} {:.1}M", node, *kb as f64 / 1024.0))
+                .collect::<Vec<_>>()
+                .join(" ");
+            let local = if total_kb > 0 {
+                format!("{:.0}%", local_kb as f64 / total_kb as f64 * 100.0)
+            } else {
+                "-".to_string()
+            };
+            let row = Row::new(vec![
+                Cell::from(launched.pid.to_string()),
+                Cell::from(format!("N{}", launched.node)),
+                Cell::from(if launched.exited { "exited" } else { "running" }),
+                Cell::from(cpu),
+                Cell::from(
+                    current_node
+                        .map(|node| format!("N{}", node))
+                        .unwrap_or_else(|| "-".to_string()),
+                )
+                .style(if off_node && !launched.exited {
+                    Style::default().fg(Color::Red)
+                } else {
+                    Style::default()
+                }),
+                Cell::from(memory),
+                Cell::from(local),
+                Cell::from(launched.command.clone()),
+            ]);
+            if launched.exited {
+                row.style(Style::default().fg(Color::DarkGray))
+            } else {
+                row
+            }
+        })
+        .collect();
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(8),
+            Constraint::Length(5),
+            Constraint::Length(8),
+            Constraint::Length(4),
+            Constraint::Length(4),
+            Constraint::Length(24),
+            Constraint::Length(6),
+            Constraint::Min(10),
+        ],
+    )
+    .header(header)
+    .block(
+        Block::default()
+            .title("Launched (r: run on the selected node, Left/Right: select node)")
+            .borders(Borders::ALL),
+    );
+    frame.render_widget(table, area);
+}
+
+fn render_action_dialog(frame: &mut Frame, app: &App, area: Rect) {
+    let Some(dialog) = &app.action_dialog else {
+        return;
+    };
+    let dialog_area = popup_area(area, 50, 30);
+    frame.render_widget(Clear, dialog_area);
+
+    let (title, current_label, new_label, hint) = match dialog.kind {
+        ActionKind::Affinity => (
+            "Set CPU affinity",
+            "Current CPUs",
+            "New CPUs",
+            "a core (3), a node (N1) or a cpulist (0-3,8)",
+        ),
+        ActionKind::MigrateMemory => (
+            "Migrate memory",
+            "Memory",
+            "Move",
+            "a destination node (1), or source and destination nodes (0 1)",
+        ),
+        ActionKind::MemoryPolicy => (
+            "Set memory policy",
+            "Current policy",
+            "New policy",
+            "bind 0-1, preferred 1, interleave 0,1 or local",
+        ),
+        ActionKind::Signal => (
+            "Send a signal",
+            "State",
+            "Signal",
+            "term, kill, int, hup, stop or cont",
+        ),
+        ActionKind::Priority => (
+            "Change priority",
+            "Current",
+            "New",
+            "a nice value (5), an I/O class (be 4, rt 0, idle) or both (-5 be 2)",
+        ),
+        ActionKind::Run(_) => (
+            "Run a command",
+            "Node",
+            "Command",
+            "plain commands run directly, pipes/globs/variables through sh -c; output is discarded",
+        ),
+    };
+    let title = match (&dialog.target, dialog.kind) {
+        (Some(target), _) => format!("{} of {}", title, target.describe()),
+        (None, ActionKind::Run(node_id)) => format!("{} bound to N{}", title, node_id),
+        (None, _) => title.to_string(),
+    };
+    let block = Block::default()
+        .title(if app.dry_run {
+            format!("{} (dry run)", title)
+        } else {
+            title
+        })
+        .borders(Borders::ALL)
+        .style(Style::default().bg(Color::Black).fg(Color::White));
+
+    let label = |text: String| Span::styled(text, Style::default().fg(Color::Gray));
+    let mut lines = vec![
+        Line::from(vec![
+            label(format!("{}: ", current_label)),
+            Span::raw(dialog.before.clone()),
+        ]),
+        Line::from(""),
+    ];
+    match dialog.stage {
+        DialogStage::Input => {
+            lines.push(Line::from(vec![
+                label(format!("{}: ", new_label)),
+                Span::raw(dialog.input.clone()),
+                Span::styled("█", Style::default().add_modifier(Modifier::SLOW_BLINK)),
+            ]));
+            lines.push(Line::from(Span::styled(
+                hint,
+                Style::default().fg(Color::DarkGray),
+            )));
+            if let Some(error) = &dialog.error {
+                lines.push(Line::from(Span::styled(
+                    error.clone(),
+                    Style::default().fg(Color::Red),
+                )));
+            }
+            lines.push(Line::from(""));
+            lines.push(Line::from(label(
+                "Enter to continue, ESC to cancel".to_string(),
+            )));
+        }
+        DialogStage::Confirm => {
+            lines.push(Line::from(vec![
+                label(format!("{}: ", new_label)),
+                Span::styled(
+                    dialog.after.clone(),
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                ),
+            ]));
+            let scope = match (dialog.kind, dialog.target.as_ref().and_then(|t| t.tid)) {
+                (ActionKind::Run(_), _) => "a new process, followed in the launched panel",
+                (ActionKind::MigrateMemory, _) => "the whole process, pages move in the background",
+                (ActionKind::MemoryPolicy, _) => {
+                    "the whole process: it gets SIGTERM and its command line is started again"
+                }
+                (ActionKind::Signal, _) => "the whole process, like kill",
+                (_, Some(_)) => "this thread only",
+                (_, None) => "every thread of the process",
+            };
+            lines.push(Line::from(label(format!("Applies to {}", scope))));
+            lines.push(Line::from(vec![
+                label("Command: ".to_string()),
+                Span::raw(dialog.command.clone()),
+            ]));
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled(
+                if app.dry_run {
+                    "Dry run: y logs the command without applying it, any other key cancels"
+                } else {
+                    "Apply? y to confirm, any other key to cancel"
+                },
+                Style::default().fg(Color::Yellow),
+            )));
+        }
+    }
+
+    frame.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .wrap(Wrap { trim: false }),
+        dialog_area,
+    );
+}
+
+fn utilization_color(utilization: f64) -> Color {
+    if utilization > 85.0 {
+        Color::Red
+    } else if utilization > 65.0 {
+        Color::Yellow
+    } else if utilization > 30.0 {
+        Color::Green
+    } else {
+        Color::Blue
+    }
+}
+
+// Share of a process's memory that is local to a node
+fn locality_color(percent: f64) -> Color {
+    if percent < 60.0 {
+        Color::Red
+    } else if percent < 85.0 {
+        Color::Yellow
+    } else {
+        Color::Green
+    }
+}
+
+fn render_node_io(frame: &mut Frame, io_devices: &[IoDevice], area: Rect) {
+    let io_block = Block::default().title("I/O Throughput");
+
+    if io_devices.is_empty() {
+        frame.render_widget(
+            Paragraph::new("No NICs or disks on this node.")
+                .style(Style::default().fg(Color::Yellow))
+                .block(io_block),
+            area,
+        );
+        return;
+    }
+
+    let total = |kind: IoDeviceKind| {
+        io_devices
+            .iter()
+            .filter(|d| d.kind == kind)
+            .fold((0.0, 0.0), |(read, write), d| {
+                (read + d.read_bytes_per_sec, write + d.write_bytes_per_sec)
+            })
+    };
+    let (net_rx, net_tx) = total(IoDeviceKind::Net);
+    let (disk_read, disk_write) = total(IoDeviceKind::Disk);
+
+    let mut lines = vec![
+        Line::from(vec![
+            Span::raw("Net   "),
+            Span::styled(
+                format!("rx {} tx {}", format_rate(net_rx), format_rate(net_tx)),
+                Style::default().fg(Color::Cyan),
+            ),
+        ]),
+        Line::from(vec![
+            Span::raw("Disk  "),
+            Span::styled(
+                format!(
+                    "rd {} wr {}",
+                    format_rate(disk_read),
+                    format_rate(disk_write)
+                ),
+                Style::default().fg(Color::Magenta),
+            ),
+        ]),
+    ];
+
+    for device in io_devices.iter().take(MAX_IO_DEVICE_LINES) {
+        let (read_label, write_label) = match device.kind {
+            IoDeviceKind::Net => ("rx", "tx"),
+            IoDeviceKind::Disk => ("rd", "wr"),
+        };
+        lines.push(Line::from(Span::styled(
+            format!(
+                "  {}: {} {} {} {}",
+                device.name,
+                read_label,
+                format_rate(device.read_bytes_per_sec),
+                write_label,
+                format_rate(device.write_bytes_per_sec)
+            ),
+            Style::default().fg(Color::Gray),
+        )));
+    }
+
+    frame.render_widget(
+        Paragraph::new(lines)
+            .style(Style::default().fg(Color::White))
+            .block(io_block),
+        area,
+    );
+}
+
+fn format_rate(bytes_per_sec: f64) -> String {
+    const UNITS: [&str; 4] = ["B/s", "KiB/s", "MiB/s", "GiB/s"];
+    let mut value = bytes_per_sec;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+fn render_mode_header(frame: &mut Frame, app: &App, area: Rect) {
+    let badge = |text: &'static str, color: Color| {
+        Span::styled(
+            text,
+            Style::default()
+                .fg(Color::Black)
+                .bg(color)
+                .add_modifier(Modifier::BOLD),
+        )
+    };
+    let mut spans = Vec::new();
+    if app.read_only {
+        spans.push(badge(" READ-ONLY ", Color::Red));
+        spans.push(Span::raw(" actions that change the system are disabled"));
+    } else if app.dry_run {
+        spans.push(badge(" DRY RUN ", Color::Yellow));
+        spans.push(Span::raw(
+            " confirmed actions are logged with their command, not applied (d to toggle)",
+        ));
+    }
+    frame.render_widget(Paragraph::new(Line::from(spans)), area);
+}
+
+fn render_filter_bar(frame: &mut Frame, app: &App, area: Rect) {
+    let mut spans = vec![
+        Span::styled("/", Style::default().fg(Color::Yellow)),
+        Span::raw(app.filter.pattern.clone()),
+    ];
+    if app.input_mode == InputMode::Search {
+        spans.push(Span::styled(
+            "█",
+            Style::default().add_modifier(Modifier::SLOW_BLINK),
+        ));
+        spans.push(Span::styled(
+            "  (name, PID, user or cmdline regex; Enter to keep, ESC to clear)",
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+    if let Some(error) = &app.filter.error {
+        // Regex errors span several lines, the last one holds the actual message
+        let first_line = error.lines().last().unwrap_or_default();
+        spans.push(Span::styled(
+            format!("  invalid regex: {}", first_line.trim()),
+            Style::default().fg(Color::Red),
+        ));
+    }
+    frame.render_widget(Paragraph::new(Line::from(spans)), area);
+}
+
+fn render_process_popup(frame: &mut Frame, app: &mut App, area: Rect) {
     // Create popup area (60% width, 70% height)
-    let popup_area = popup_area(frame.area(), 60, 70);
+    let popup_area = popup_area(area, 60, 70);
 
     // Clear the area
     frame.render_widget(Clear, popup_area);
 
-    // Create the popup block
+    let (target_label, scope) = match app.popup_state.target {
+        PopupTarget::Core(cpu_core_id) => (format!("CPU Core {}", cpu_core_id), "core"),
+        PopupTarget::Node(node_id) => (format!("NUMA Node {}", node_id), "node"),
+    };
+
+    // Create the popup block, with the key bindings along the bottom border
     let popup_block = Block::default()
-        .title(format!(
-            "Processes on CPU Core {} (Press ESC to close)",
-            app.popup_state.cpu_core_id
+        .title(format!("Tasks on {} (live)", target_label))
+        .title_bottom(format!(
+            " Enter details | s/S sort | g grouping | 1-8 columns | k {} kernel threads | ESC close ",
+            if app.hide_kernel_threads {
+                "show"
+            } else {
+                "hide"
+            }
         ))
         .borders(Borders::ALL)
         .style(Style::default().bg(Color::Black).fg(Color::White));
 
     frame.render_widget(popup_block, popup_area);
 
-    // Create inner area for the process list
-    let inner_area = Layout::default()
+    // Column picker on top, process table below
+    let [columns_area, inner_area] = Layout::vertical([Constraint::Length(1), Constraint::Min(0)])
         .margin(1)
-        .constraints([Constraint::Min(0)])
-        .split(popup_area)[0];
+        .areas(popup_area);
+    frame.render_widget(popup_column_picker(&app.popup_state.columns), columns_area);
 
-    if app.popup_state.processes.is_empty() {
+    let popup_rows = app.popup_rows();
+    if popup_rows.is_empty() {
         // Show message when no processes are found
-        let no_processes_msg = Paragraph::new("No processes found with affinity to this CPU core")
-            .style(Style::default().fg(Color::Yellow))
-            .block(Block::default().borders(Borders::NONE));
+        let no_processes_msg = Paragraph::new(if app.filter.is_active() {
+            format!("No tasks on this {} match the filter", scope)
+        } else {
+            format!("No tasks last ran on this {}", scope)
+        })
+        .style(Style::default().fg(Color::Yellow))
+        .block(Block::default().borders(Borders::NONE));
         frame.render_widget(no_processes_msg, inner_area);
-    } else {
-        // Create process list items
-        let process_items: Vec<ListItem> = app
-            .popup_state
-            .processes
+        return;
+    }
+
+    let cpu_nodes = app.cpu_node_map();
+    let node_target = match app.popup_state.target {
+        PopupTarget::Node(node_id) => Some(node_id),
+        PopupTarget::Core(_) => None,
+    };
+    let columns = &app.popup_state.columns;
+    let show_cmdline = columns.contains(&PopupColumn::Cmdline);
+    let optional_columns: Vec<PopupColumn> = columns
+        .iter()
+        .copied()
+        .filter(|c| *c != PopupColumn::Cmdline)
+        .collect();
+
+    // Header, with an arrow on the sort column
+    let sort_label = |key: PopupSortKey, label: &str| {
+        if app.popup_state.sort_key == key {
+            let arrow = if app.popup_state.sort_descending {
+                "▼"
+            } else {
+                "▲"
+            };
+            format!("{}{}", label, arrow)
+        } else {
+            label.to_string()
+        }
+    };
+    let mut header_cells = vec![
+        Cell::from(""),
+        Cell::from(sort_label(PopupSortKey::Cpu, "CPU%")),
+    ];
+    header_cells.extend(
+        optional_columns
             .iter()
-            .map(|process| {
-                let line = Line::from(vec![
-                    Span::raw(format!("PID {}: ", process.pid)),
-                    Span::styled(
-                        format!("{}", process.name),
-                        Style::default().fg(Color::Cyan),
-                    ),
-                ]);
-
-                ListItem::new(line)
+            .map(|c| Cell::from(sort_label(PopupSortKey::Column(*c), c.label()))),
+    );
+    header_cells.extend([
+        Cell::from(sort_label(PopupSortKey::Tid, "TID")),
+        Cell::from(sort_label(PopupSortKey::Name, "Name")),
+    ]);
+    if node_target.is_some() {
+        header_cells.push(Cell::from("On"));
+    }
+    header_cells.extend([Cell::from("CPUs"), Cell::from("Mems"), Cell::from("")]);
+    if show_cmdline {
+        header_cells.push(Cell::from(sort_label(
+            PopupSortKey::Column(PopupColumn::Cmdline),
+            PopupColumn::Cmdline.label(),
+        )));
+    }
+    let header = Row::new(header_cells).style(Style::default().fg(Color::Yellow));
+
+    let rows: Vec<Row<'static>> = popup_rows
+        .iter()
+        .map(|row| match row {
+            PopupRow::Process(threads) => {
+                let mut cells = process_row_cells(
+                    threads,
+                    &optional_columns,
+                    node_target.map(|node_id| {
+                        (
+                            node_id,
+                            app.popup_state.process_memory_kb.get(&threads[0].tgid),
+                        )
+                    }),
+                    scope,
+                );
+                if show_cmdline {
+                    cells.push(Cell::from(popup_column_text(
+                        threads[0],
+                        PopupColumn::Cmdline,
+                    )));
+                }
+                Row::new(cells)
+            }
+            PopupRow::Task(task) => {
+                let change = app.popup_state.changes.get(&task.tid);
+                let mut cells = task_row_cells(
+                    task,
+                    &cpu_nodes,
+                    &optional_columns,
+                    node_target.is_some(),
+                    app.popup_state.group_by_process,
+                    change,
+                );
+                if show_cmdline {
+                    cells.push(Cell::from(popup_column_text(task, PopupColumn::Cmdline)));
+                }
+                // Departed tasks are shown once more, dimmed, so the user notices them leaving
+                if change == Some(&TaskChange::Departed) {
+                    Row::new(cells).style(
+                        Style::default()
+                            .fg(Color::DarkGray)
+                            .add_modifier(Modifier::CROSSED_OUT),
+                    )
+                } else {
+                    Row::new(cells)
+                }
+            }
+        })
+        .collect();
+    drop(popup_rows);
+
+    let mut widths = vec![Constraint::Length(1), Constraint::Length(7)];
+    widths.extend(optional_columns.iter().map(|c| match c {
+        PopupColumn::User => Constraint::Length(9),
+        PopupColumn::State => Constraint::Length(6),
+        PopupColumn::Rss => Constraint::Length(9),
+        PopupColumn::Started => Constraint::Length(8),
+        _ => Constraint::Length(7),
+    }));
+    widths.extend([Constraint::Length(10), Constraint::Length(18)]);
+    if node_target.is_some() {
+        widths.push(Constraint::Length(4));
+    }
+    widths.extend([
+        Constraint::Length(10),
+        Constraint::Length(6),
+        Constraint::Min(24),
+    ]);
+    if show_cmdline {
+        widths.push(Constraint::Fill(1));
+    }
+
+    let table = Table::new(rows, widths)
+        .header(header)
+        .style(Style::default().fg(Color::White))
+        .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+
+    frame.render_stateful_widget(table, inner_area, &mut app.popup_state.table_state);
+}
+
+// "1 User  2 State  3 Prio ...", enabled columns highlighted
+fn popup_column_picker(enabled: &[PopupColumn]) -> Line<'static> {
+    let mut spans = vec![Span::styled("Columns: ", Style::default().fg(Color::Gray))];
+    for (index, column) in PopupColumn::ALL.iter().enumerate() {
+        let style = if enabled.contains(column) {
+            Style::default()
+                .fg(Color::Black)
+                .bg(Color::Cyan)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Color::DarkGray)
+        };
+        spans.push(Span::styled(
+            format!("{} {}", index + 1, column.label()),
+            style,
+        ));
+        spans.push(Span::raw(" "));
+    }
+    Line::from(spans)
+}
+
+fn popup_column_text(task: &ProcessInfo, column: PopupColumn) -> String {
+    match column {
+        PopupColumn::User => task.user.clone(),
+        PopupColumn::State => task.state.to_string(),
+        PopupColumn::Priority => task.priority.to_string(),
+        PopupColumn::Nice => task.nice.to_string(),
+        PopupColumn::Policy => sched_policy_name(task.policy).to_string(),
+        PopupColumn::Rss => format!("{:.1}M", task.rss_kb as f64 / 1024.0),
+        PopupColumn::Started => format_start_time(task.start_time),
+        // ps shows kernel threads' empty command lines as [comm]
+        PopupColumn::Cmdline if task.cmdline.is_empty() => format!("[{}]", task.process_name),
+        PopupColumn::Cmdline => task.cmdline.clone(),
+    }
+}
+
+// "14:03" for tasks started within the last day, "Mar05" before that, like ps
+fn format_start_time(start_time: u64) -> String {
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    let time = start_time as libc::time_t;
+    // SAFETY: localtime_r only writes to the tm we pass in
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    if unsafe { libc::localtime_r(&time, &mut tm) }.is_null() {
+        return "?".to_string();
+    }
+
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    if now.saturating_sub(start_time) < 24 * 60 * 60 {
+        format!("{:02}:{:02}", tm.tm_hour, tm.tm_min)
+    } else {
+        format!("{}{:02}", MONTHS[tm.tm_mon as usize % 12], tm.tm_mday)
+    }
+}
+
+// Grouped header: "  12.5%  PID 1234  java  (3 threads on this core)  1228.8 MiB on N1 (85%)".
+// Only the per-process columns (user, RSS) are filled in.
+fn process_row_cells(
+    threads: &[&ProcessInfo],
+    optional_columns: &[PopupColumn],
+    residency: Option<(u32, Option<&BTreeMap<u32, u64>>)>,
+    scope: &str,
+) -> Vec<Cell<'static>> {
+    let leader = threads[0];
+    let process_cpu: f64 = threads.iter().map(|t| t.cpu_percent).sum();
+
+    let mut cells = vec![
+        Cell::from(""),
+        Cell::from(format!("{:.1}%", process_cpu))
+            .style(Style::default().fg(utilization_color(process_cpu))),
+    ];
+    cells.extend(optional_columns.iter().map(|c| match c {
+        PopupColumn::User | PopupColumn::Rss => Cell::from(popup_column_text(leader, *c)),
+        _ => Cell::from(""),
+    }));
+    cells.extend([
+        Cell::from(format!("PID {}", leader.tgid)),
+        Cell::from(leader.process_name.clone()).style(
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        ),
+    ]);
+    if residency.is_some() {
+        cells.push(Cell::from(""));
+    }
+    cells.extend([Cell::from(""), Cell::from("")]);
+
+    let mut info = vec![Span::styled(
+        format!(
+            "{} thread{} on this {}",
+            threads.len(),
+            if threads.len() == 1 { "" } else { "s" },
+            scope
+        ),
+        Style::default().fg(Color::Gray),
+    )];
+    if let Some((node_id, Some(node_memory_kb))) = residency {
+        info.push(node_residency_span(node_id, node_memory_kb));
+    }
+    cells.push(Cell::from(Line::from(info)));
+    cells
+}
+
+// "+  12.5%  root  S  1234  worker-3  CPUs 0-7  Mems 0  [flags]", + marking a newcomer
+fn task_row_cells(
+    task: &ProcessInfo,
+    cpu_nodes: &HashMap<u32, u32>,
+    optional_columns: &[PopupColumn],
+    show_cpu: bool,
+    grouped: bool,
+    change: Option<&TaskChange>,
+) -> Vec<Cell<'static>> {
+    // Departed rows are dimmed as a whole, so their cells must not set colors of their own
+    let departed = change == Some(&TaskChange::Departed);
+    let style = |style: Style| if departed { Style::default() } else { style };
+
+    let marker = match change {
+        Some(TaskChange::Appeared) => {
+            Cell::from("+").style(style(Style::default().fg(Color::Green)))
+        }
+        Some(TaskChange::Departed) => Cell::from("-").style(Style::default().fg(Color::Red)),
+        None => Cell::from(""),
+    };
+    let mut cells = vec![
+        marker,
+        Cell::from(format!("{:.1}%", task.cpu_percent)).style(style(
+            Style::default().fg(utilization_color(task.cpu_percent)),
+        )),
+    ];
+    cells.extend(optional_columns.iter().map(|c| {
+        Cell::from(popup_column_text(task, *c)).style(style(Style::default().fg(Color::Yellow)))
+    }));
+    cells.extend([
+        Cell::from(task.tid.to_string()),
+        Cell::from(if grouped {
+            format!("  {}", task.thread_name)
+        } else {
+            task.thread_name.clone()
+        })
+        .style(style(Style::default().fg(Color::Cyan))),
+    ]);
+    if show_cpu {
+        cells.push(Cell::from(task.cpu.to_string()));
+    }
+    cells.extend([
+        Cell::from(format_cpulist(&task.cpus_allowed)),
+        Cell::from(format_cpulist(&task.mems_allowed)),
+    ]);
+
+    let mut info = Vec::new();
+    let flags = affinity_flags(task, cpu_nodes);
+    if !flags.is_empty() {
+        info.push(Span::styled(
+            format!("[{}] ", flags.join(", ")),
+            style(Style::default().fg(Color::Red)),
+        ));
+    }
+    if !grouped {
+        info.push(Span::styled(
+            format!("in PID {} ({})", task.tgid, task.process_name),
+            style(Style::default().fg(Color::Gray)),
+        ));
+    }
+    cells.push(Cell::from(Line::from(info)));
+    cells
+}
+
+// "  1228.8 MiB on N1 (85%)": how much of the process's memory lives on the node
+fn node_residency_span(node_id: u32, node_memory_kb: &BTreeMap<u32, u64>) -> Span<'static> {
+    let total_kb: u64 = node_memory_kb.values().sum();
+    let on_node_kb = node_memory_kb.get(&node_id).copied().unwrap_or(0);
+    let percent = if total_kb > 0 {
+        on_node_kb as f64 / total_kb as f64 * 100.0
+    } else {
+        0.0
+    };
+    Span::styled(
+        format!(
+            "  {:.1} MiB on N{} ({:.0}%)",
+            on_node_kb as f64 / 1024.0,
+            node_id,
+            percent
+        ),
+        Style::default().fg(locality_color(percent)),
+    )
+}
+
+// Placement problems worth pointing out next to a task
+fn affinity_flags(process: &ProcessInfo, cpu_nodes: &HashMap<u32, u32>) -> Vec<&'static str> {
+    let mut flags = Vec::new();
+    if process.cpus_span_nodes(cpu_nodes) {
+        flags.push("CPUs span nodes");
+    }
+    if process.runs_outside_mems_allowed(cpu_nodes) {
+        flags.push("runs off its mem nodes");
+    }
+    flags
+}
+
+fn render_process_table(frame: &mut Frame, app: &mut App, area: Rect) {
+    let table_block = Block::default()
+        .title("Process Memory per NUMA Node (Enter for mappings, p or ESC to go back)")
+        .title(
+            Line::from(format!(
+                " Automatic NUMA balancing: {} ",
+                describe_numa_balancing_mode(app.numa_balancing_mode)
+            ))
+            .right_aligned(),
+        )
+        .borders(Borders::ALL);
+
+    let processes = app.visible_process_memory();
+    if processes.is_empty() {
+        frame.render_widget(
+            Paragraph::new(if app.filter.is_active() {
+                "No processes match the filter"
+            } else {
+                "No processes with readable numa_maps found"
             })
-            .collect();
+            .style(Style::default().fg(Color::Yellow))
+            .block(table_block),
+            area,
+        );
+        return;
+    }
+
+    let node_ids: Vec<u32> = app.numa_nodes.iter().map(|n| n.id).collect();
+    let cpu_nodes = app.cpu_node_map();
+
+    let mut header_cells = vec![Cell::from("PID"), Cell::from("Name")];
+    header_cells.extend(node_ids.iter().map(|id| Cell::from(format!("N{} MiB", id))));
+    header_cells.extend([
+        Cell::from("Total MiB"),
+        Cell::from("Home"),
+        Cell::from("On Home"),
+        Cell::from("CPUs"),
+        Cell::from("Mems"),
+        Cell::from("Pref"),
+        Cell::from("On"),
+        Cell::from("Migrated"),
+        Cell::from("Flags"),
+    ]);
+    let header = Row::new(header_cells).style(Style::default().fg(Color::Yellow));
+
+    let rows: Vec<Row> = processes
+        .iter()
+        .map(|process| {
+            let home_percent = process.home_node_percent();
+
+            let mut cells = vec![
+                Cell::from(process.info.tgid.to_string()),
+                Cell::from(process.info.process_name.clone())
+                    .style(Style::default().fg(Color::Cyan)),
+            ];
+            cells.extend(node_ids.iter().map(|id| {
+                let kb = process.node_memory_kb.get(id).copied().unwrap_or(0);
+                Cell::from(format!("{:.1}", kb as f64 / 1024.0))
+            }));
+            cells.extend([
+                Cell::from(format!("{:.1}", process.total_kb() as f64 / 1024.0)),
+                Cell::from(
+                    process
+                        .home_node()
+                        .map(|id| format!("N{}", id))
+                        .unwrap_or_default(),
+                ),
+                Cell::from(format!("{:.0}%", home_percent))
+                    .style(Style::default().fg(locality_color(home_percent))),
+                Cell::from(format_cpulist(&process.info.cpus_allowed)),
+                Cell::from(format_cpulist(&process.info.mems_allowed)),
+            ]);
+            cells.extend(numa_balancing_cells(
+                process.numa_sched.as_ref(),
+                cpu_nodes.get(&process.info.cpu).copied(),
+            ));
+            cells.push(
+                Cell::from(affinity_flags(&process.info, &cpu_nodes).join(", "))
+                    .style(Style::default().fg(Color::Red)),
+            );
+            Row::new(cells)
+        })
+        .collect();
+
+    let mut widths = vec![Constraint::Length(8), Constraint::Length(16)];
+    widths.extend(node_ids.iter().map(|_| Constraint::Length(10)));
+    widths.extend([
+        Constraint::Length(10),
+        Constraint::Length(5),
+        Constraint::Length(8),
+        Constraint::Length(12),
+        Constraint::Length(8),
+        Constraint::Length(5),
+        Constraint::Length(5),
+        Constraint::Length(9),
+        Constraint::Min(20),
+    ]);
+
+    let table = Table::new(rows, widths)
+        .header(header)
+        .block(table_block)
+        .style(Style::default().fg(Color::White))
+        .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED));
 
-        let process_list = List::new(process_items)
-            .block(Block::default().borders(Borders::NONE))
-            .style(Style::default().fg(Color::White));
+    frame.render_stateful_widget(table, area, &mut app.process_table_state);
+}
+
+// Preferred node, current node and pages migrated by automatic NUMA balancing. The
+// preferred node is highlighted when the task currently runs elsewhere.
+fn numa_balancing_cells(
+    numa_sched: Option<&NumaSchedStats>,
+    fallback_node: Option<u32>,
+) -> [Cell<'static>; 3] {
+    let Some(stats) = numa_sched else {
+        return [Cell::from("-"), Cell::from("-"), Cell::from("-")];
+    };
+
+    let current_node = stats.current_node.or(fallback_node);
+    let preferred_style = match (stats.preferred_node, current_node) {
+        (Some(preferred), Some(current)) if preferred != current => Style::default().fg(Color::Red),
+        (Some(_), Some(_)) => Style::default().fg(Color::Green),
+        _ => Style::default().fg(Color::DarkGray),
+    };
+    let format_node =
+        |node: Option<u32>| node.map(|id| format!("N{}", id)).unwrap_or("-".to_string());
+
+    [
+        Cell::from(format_node(stats.preferred_node)).style(preferred_style),
+        Cell::from(format_node(current_node)),
+        Cell::from(stats.pages_migrated.to_string()),
+    ]
+}
+
+fn render_migration_table(frame: &mut Frame, app: &mut App, area: Rect) {
+    let table_block = Block::default()
+        .title(format!(
+            "Thread Migrations over {} samples (k to {} kernel threads, m or ESC to go back)",
+            app.migration_tracker.samples,
+            if app.hide_kernel_threads {
+                "show"
+            } else {
+                "hide"
+            }
+        ))
+        .borders(Borders::ALL);
 
-        frame.render_widget(process_list, inner_area);
+    let threads = app.visible_migrations();
+    if threads.is_empty() {
+        frame.render_widget(
+            Paragraph::new(if app.filter.is_active() {
+                "No migrating threads match the filter"
+            } else {
+                "No thread has changed CPU between samples yet"
+            })
+            .style(Style::default().fg(Color::Yellow))
+            .block(table_block),
+            area,
+        );
+        return;
     }
+
+    let cpu_nodes = app.cpu_node_map();
+    let header = Row::new(vec![
+        Cell::from("TID"),
+        Cell::from("PID"),
+        Cell::from("Process"),
+        Cell::from("Thread"),
+        Cell::from("CPU"),
+        Cell::from("Node"),
+        Cell::from("Core Migr."),
+        Cell::from("Node Migr."),
+    ])
+    .style(Style::default().fg(Color::Yellow));
+
+    let rows: Vec<Row> = threads
+        .iter()
+        .map(|thread| {
+            let node_style = if thread.node_migrations > 0 {
+                Style::default().fg(Color::Red)
+            } else {
+                Style::default()
+            };
+            Row::new(vec![
+                Cell::from(thread.tid.to_string()),
+                Cell::from(thread.tgid.to_string()),
+                Cell::from(thread.process_name.clone()).style(Style::default().fg(Color::Cyan)),
+                Cell::from(thread.thread_name.clone()),
+                Cell::from(thread.cpu.to_string()),
+                Cell::from(
+                    cpu_nodes
+                        .get(&thread.cpu)
+                        .map(|node_id| format!("N{}", node_id))
+                        .unwrap_or_default(),
+                ),
+                Cell::from(thread.core_migrations.to_string()),
+                Cell::from(thread.node_migrations.to_string()).style(node_style),
+            ])
+        })
+        .collect();
+
+    let widths = [
+        Constraint::Length(8),
+        Constraint::Length(8),
+        Constraint::Length(16),
+        Constraint::Length(16),
+        Constraint::Length(5),
+        Constraint::Length(5),
+        Constraint::Length(11),
+        Constraint::Length(11),
+    ];
+
+    let table = Table::new(rows, widths)
+        .header(header)
+        .block(table_block)
+        .style(Style::default().fg(Color::White))
+        .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+
+    frame.render_stateful_widget(table, area, &mut app.migration_table_state);
+}
+
+fn render_suggestion_table(frame: &mut Frame, app: &mut App, area: Rect) {
+    let table_block = Block::default()
+        .title("Rebalancing Suggestions (Enter to review and apply, b or ESC to go back)")
+        .borders(Borders::ALL);
+
+    let suggestions = app.visible_suggestions();
+    if suggestions.is_empty() {
+        frame.render_widget(
+            Paragraph::new(if app.filter.is_active() {
+                "No suggestions match the filter"
+            } else {
+                "No process would gain much locality from a move"
+            })
+            .style(Style::default().fg(Color::Yellow))
+            .block(table_block),
+            area,
+        );
+        return;
+    }
+
+    let header = Row::new(vec![
+        Cell::from("PID"),
+        Cell::from("Name"),
+        Cell::from("Memory MiB"),
+        Cell::from("Local now"),
+        Cell::from("After"),
+        Cell::from("Gain MiB"),
+        Cell::from("Suggestion"),
+    ])
+    .style(Style::default().fg(Color::Yellow));
+
+    let rows: Vec<Row> = suggestions
+        .iter()
+        .map(|suggestion| {
+            Row::new(vec![
+                Cell::from(suggestion.pid.to_string()),
+                Cell::from(suggestion.name.clone()).style(Style::default().fg(Color::Cyan)),
+                Cell::from(format!("{:.1}", suggestion.total_kb as f64 / 1024.0)),
+                Cell::from(format!("{:.0}%", suggestion.locality_now * 100.0))
+                    .style(Style::default().fg(Color::Red)),
+                Cell::from(format!("{:.0}%", suggestion.locality_after * 100.0))
+                    .style(Style::default().fg(Color::Green)),
+                Cell::from(format!(
+                    "+{:.1}",
+                    suggestion.gained_local_kb() as f64 / 1024.0
+                )),
+                Cell::from(suggestion.describe()),
+            ])
+        })
+        .collect();
+
+    let widths = [
+        Constraint::Length(8),
+        Constraint::Length(16),
+        Constraint::Length(11),
+        Constraint::Length(10),
+        Constraint::Length(6),
+        Constraint::Length(9),
+        Constraint::Min(20),
+    ];
+
+    let table = Table::new(rows, widths)
+        .header(header)
+        .block(table_block)
+        .style(Style::default().fg(Color::White))
+        .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+
+    frame.render_stateful_widget(table, area, &mut app.suggestion_table_state);
+}
+
+fn render_process_detail(frame: &mut Frame, app: &mut App, area: Rect) {
+    let [identity_area, balancing_area, timeline_area, mappings_area] = Layout::vertical([
+        Constraint::Length(7),
+        Constraint::Length(4),
+        Constraint::Length(4),
+        Constraint::Min(0),
+    ])
+    .areas(area);
+    render_task_identity(frame, app, identity_area);
+    render_node_timeline(frame, &app.process_detail.node_timeline, timeline_area);
+    render_numa_balancing_summary(
+        frame,
+        app.process_detail.numa_sched.as_ref(),
+        app.numa_balancing_mode,
+        balancing_area,
+    );
+
+    let detail = &mut app.process_detail;
+    let detail_block = Block::default()
+        .title(format!("numa_maps of PID {} ({})", detail.pid, detail.name))
+        .borders(Borders::ALL);
+
+    if detail.mappings.is_empty() {
+        let text = match &detail.mappings_error {
+            Some(error) => format!("Can't read numa_maps: {}", error),
+            None => "No mappings found".to_string(),
+        };
+        frame.render_widget(
+            Paragraph::new(text)
+                .style(Style::default().fg(Color::Yellow))
+                .block(detail_block),
+            mappings_area,
+        );
+        return;
+    }
+
+    let header = Row::new(vec![
+        Cell::from("Address"),
+        Cell::from("Policy"),
+        Cell::from("Backing"),
+        Cell::from("Pages per Node"),
+        Cell::from("Placement"),
+    ])
+    .style(Style::default().fg(Color::Yellow));
+
+    let rows: Vec<Row> = detail
+        .mappings
+        .iter()
+        .map(|mapping| {
+            let backing = match &mapping.backing {
+                MappingBacking::File(path) => path.clone(),
+                MappingBacking::Heap => "[heap]".to_string(),
+                MappingBacking::Stack => "[stack]".to_string(),
+                MappingBacking::Anon => "[anon]".to_string(),
+            };
+            let pages = mapping
+                .pages_per_node
+                .iter()
+                .map(|(node_id, pages)| format!("N{}={}", node_id, pages))
+                .collect::<Vec<_>>()
+                .join(" ");
+
+            Row::new(vec![
+                Cell::from(format!("{:012x}", mapping.address)),
+                Cell::from(mapping.policy.clone()),
+                Cell::from(backing).style(Style::default().fg(Color::Cyan)),
+                Cell::from(pages),
+                Cell::from(placement_bar(mapping)),
+            ])
+        })
+        .collect();
+
+    let widths = [
+        Constraint::Length(14),
+        Constraint::Length(16),
+        Constraint::Min(20),
+        Constraint::Length(24),
+        Constraint::Length(PLACEMENT_BAR_WIDTH as u16),
+    ];
+
+    let table = Table::new(rows, widths)
+        .header(header)
+        .block(detail_block)
+        .style(Style::default().fg(Color::White))
+        .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+
+    frame.render_stateful_widget(table, mappings_area, &mut detail.table_state);
+}
+
+// Who the task is, where it may run and allocate, and where its memory is
+fn render_task_identity(frame: &mut Frame, app: &App, area: Rect) {
+    let detail = &app.process_detail;
+    let block = Block::default()
+        .title(format!(
+            "PID {} TID {} ({}) (Press ESC to go back)",
+            detail.pid, detail.tid, detail.name
+        ))
+        .borders(Borders::ALL);
+
+    let Some(task) = &detail.task else {
+        frame.render_widget(
+            Paragraph::new("The task has exited")
+                .style(Style::default().fg(Color::Yellow))
+                .block(block),
+            area,
+        );
+        return;
+    };
+
+    let cpu_nodes = app.cpu_node_map();
+    let label = |text: &'static str| Span::styled(text, Style::default().fg(Color::Gray));
+    let format_nodes = |nodes: &[u32]| {
+        nodes
+            .iter()
+            .map(|id| format!("N{}", id))
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    let mut allowed_nodes: Vec<u32> = task
+        .cpus_allowed
+        .iter()
+        .filter_map(|cpu| cpu_nodes.get(cpu).copied())
+        .collect();
+    allowed_nodes.sort_unstable();
+    allowed_nodes.dedup();
+
+    let identity = Line::from(vec![
+        Span::styled(
+            task.thread_name.clone(),
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Span::raw(format!(" in {}  ", task.process_name)),
+        label("user "),
+        Span::raw(format!("{}  ", task.user)),
+        label("state "),
+        Span::raw(format!("{}  ", task.state)),
+        label("prio "),
+        Span::raw(format!("{}  ", task.priority)),
+        label("nice "),
+        Span::raw(format!("{}  ", task.nice)),
+        label("policy "),
+        Span::raw(format!("{}  ", sched_policy_name(task.policy))),
+        label("started "),
+        Span::raw(format_start_time(task.start_time)),
+    ]);
+    let cmdline = Line::from(vec![
+        label("cmdline "),
+        Span::raw(popup_column_text(task, PopupColumn::Cmdline)),
+    ]);
+    let cgroup = Line::from(vec![label("cgroup  "), Span::raw(detail.cgroup.clone())]);
+
+    let current_node = cpu_nodes.get(&task.cpu).copied();
+    let mut placement = vec![
+        label("CPUs "),
+        Span::raw(format!(
+            "{} ({})  ",
+            format_cpulist(&task.cpus_allowed),
+            format_nodes(&allowed_nodes)
+        )),
+        label("Mems "),
+        Span::raw(format!("{}  ", format_cpulist(&task.mems_allowed))),
+        label("on CPU "),
+        Span::styled(
+            format!(
+                "{} (N{})",
+                task.cpu,
+                current_node.map_or("?".to_string(), |id| id.to_string())
+            ),
+            Style::default().fg(current_node.map_or(Color::Gray, node_color)),
+        ),
+    ];
+    let flags = affinity_flags(task, &cpu_nodes);
+    if !flags.is_empty() {
+        placement.push(Span::styled(
+            format!("  [{}]", flags.join(", ")),
+            Style::default().fg(Color::Red),
+        ));
+    }
+
+    let node_memory_kb = get_node_memory_kb(&detail.mappings);
+    let total_kb: u64 = node_memory_kb.values().sum();
+    let mut memory = vec![
+        label("policy "),
+        Span::raw(format!(
+            "{}  ",
+            dominant_policy(&detail.mappings).unwrap_or("default")
+        )),
+        label("memory "),
+    ];
+    memory.extend(node_memory_kb.iter().map(|(node_id, kb)| {
+        Span::styled(
+            format!("N{} {:.1} MiB  ", node_id, *kb as f64 / 1024.0),
+            Style::default().fg(node_color(*node_id)),
+        )
+    }));
+    memory.push(Span::raw(format!(
+        "total {:.1} MiB, RSS {:.1} MiB",
+        total_kb as f64 / 1024.0,
+        task.rss_kb as f64 / 1024.0
+    )));
+
+    let lines = vec![
+        identity,
+        cmdline,
+        cgroup,
+        Line::from(placement),
+        Line::from(memory),
+    ];
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+// One cell per tick in the color of the node the task ran on, newest on the right
+fn render_node_timeline(frame: &mut Frame, timeline: &VecDeque<Option<u32>>, area: Rect) {
+    let block = Block::default()
+        .title(format!(
+            "Node timeline (last {} ticks)",
+            DETAIL_TIMELINE_TICKS
+        ))
+        .borders(Borders::ALL);
+
+    let cells: Vec<Span> = timeline
+        .iter()
+        .map(|node| match node {
+            Some(node_id) => Span::styled("█", Style::default().fg(node_color(*node_id))),
+            None => Span::styled("·", Style::default().fg(Color::DarkGray)),
+        })
+        .collect();
+
+    // Legend of the nodes that appear, with how many ticks were spent on each
+    let mut ticks_per_node: BTreeMap<u32, usize> = BTreeMap::new();
+    for node_id in timeline.iter().flatten() {
+        *ticks_per_node.entry(*node_id).or_insert(0) += 1;
+    }
+    let legend: Vec<Span> = ticks_per_node
+        .iter()
+        .map(|(node_id, ticks)| {
+            Span::styled(
+                format!("█ N{}: {} ticks  ", node_id, ticks),
+                Style::default().fg(node_color(*node_id)),
+            )
+        })
+        .collect();
+
+    frame.render_widget(
+        Paragraph::new(vec![Line::from(cells), Line::from(legend)]).block(block),
+        area,
+    );
+}
+
+fn render_numa_balancing_summary(
+    frame: &mut Frame,
+    numa_sched: Option<&NumaSchedStats>,
+    mode: Option<u32>,
+    area: Rect,
+) {
+    let block = Block::default()
+        .title(format!(
+            "Automatic NUMA Balancing ({})",
+            describe_numa_balancing_mode(mode)
+        ))
+        .borders(Borders::ALL);
+
+    let Some(stats) = numa_sched else {
+        frame.render_widget(
+            Paragraph::new("No NUMA statistics in /proc/PID/sched (needs CONFIG_NUMA_BALANCING)")
+                .style(Style::default().fg(Color::Yellow))
+                .block(block),
+            area,
+        );
+        return;
+    };
+
+    let format_node = |node: Option<u32>| {
+        node.map(|id| format!("N{}", id))
+            .unwrap_or("none".to_string())
+    };
+    let preferred_color = match (stats.preferred_node, stats.current_node) {
+        (Some(preferred), Some(current)) if preferred != current => Color::Red,
+        (Some(_), Some(_)) => Color::Green,
+        _ => Color::Gray,
+    };
+
+    let summary = Line::from(vec![
+        Span::raw("Preferred "),
+        Span::styled(
+            format_node(stats.preferred_node),
+            Style::default().fg(preferred_color),
+        ),
+        Span::raw(format!(
+            ", running on {}, NUMA group {}, {} pages migrated, {} faults",
+            format_node(stats.current_node),
+            stats.numa_group_id,
+            stats.pages_migrated,
+            stats.total_faults
+        )),
+    ]);
+    let mut fault_spans = vec![Span::raw("Faults (private/shared)  ")];
+    fault_spans.extend(stats.node_faults.iter().map(|(node_id, faults)| {
+        Span::styled(
+            format!(
+                "N{}: task {}/{} group {}/{}   ",
+                node_id,
+                faults.task_private,
+                faults.task_shared,
+                faults.group_private,
+                faults.group_shared
+            ),
+            Style::default().fg(node_color(*node_id)),
+        )
+    }));
+    let faults = Line::from(fault_spans);
+
+    frame.render_widget(Paragraph::new(vec![summary, faults]).block(block), area);
+}
+
+// One colored segment per node, proportional to the share of the mapping's pages on it
+fn placement_bar(mapping: &NumaMapping) -> Line<'static> {
+    let total_pages = mapping.total_pages();
+    if total_pages == 0 {
+        return Line::from(Span::styled(
+            "not resident",
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+
+    let mut spans = Vec::new();
+    let mut used_width = 0;
+    for (node_id, pages) in &mapping.pages_per_node {
+        let width =
+            ((*pages as f64 / total_pages as f64) * PLACEMENT_BAR_WIDTH as f64).round() as usize;
+        let width = width.min(PLACEMENT_BAR_WIDTH - used_width);
+        used_width += width;
+        spans.push(Span::styled(
+            "█".repeat(width),
+            Style::default().fg(node_color(*node_id)),
+        ));
+    }
+    Line::from(spans)
+}
+
+// One color per node ID, shared by the chart, the timeline and the placement bars
+fn node_color(node_id: u32) -> Color {
+    const NODE_COLORS: [Color; 6] = [
+        Color::Cyan,
+        Color::Magenta,
+        Color::Green,
+        Color::Yellow,
+        Color::Blue,
+        Color::Red,
+    ];
+    NODE_COLORS[node_id as usize % NODE_COLORS.len()]
 }
 
 fn popup_area(area: Rect, percent_x: u16, percent_y: u16) -> Rect {